@@ -0,0 +1,60 @@
+//! Annotations drawn on the plot, which do not affect portal linking.
+
+use serde::{Deserialize, Serialize};
+
+use crate::{BlockRegion, WorldPos};
+
+/// Freeform line drawn on the plot, such as a planned tunnel or ice road.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Route {
+    /// User-friendly name.
+    pub name: String,
+    /// Color.
+    pub color: [u8; 3],
+    /// Points along the route, in order.
+    pub points: Vec<WorldPos>,
+}
+
+impl Default for Route {
+    fn default() -> Self {
+        Self {
+            name: String::new(),
+            color: [255, 255, 255],
+            points: vec![],
+        }
+    }
+}
+
+impl Route {
+    /// Returns a nonempty human-friendly name for the route.
+    pub fn display_name(&self) -> &str {
+        if self.name.is_empty() {
+            "<unnamed>"
+        } else {
+            &self.name
+        }
+    }
+}
+
+/// Labeled rectangle drawn on the plot, such as a base, biome, or no-build
+/// zone.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Area {
+    /// User-friendly name.
+    pub name: String,
+    /// Color.
+    pub color: [u8; 3],
+    /// Region covered by the area.
+    pub region: BlockRegion,
+}
+
+impl Area {
+    /// Returns a nonempty human-friendly name for the area.
+    pub fn display_name(&self) -> &str {
+        if self.name.is_empty() {
+            "<unnamed>"
+        } else {
+            &self.name
+        }
+    }
+}
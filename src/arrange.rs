@@ -0,0 +1,220 @@
+//! Alignment and distribution of groups of portals.
+
+use itertools::Itertools;
+
+use crate::{
+    Axis, BlockPos, ConvertDimension, Dimension, Entity, Portal, WorldPortals, WorldRegion,
+};
+
+/// Maximum distance, in blocks, that [`link_offset()`] will move a portal away
+/// from the position that corresponds exactly to its target.
+const LINK_SEARCH_RADIUS: i64 = 16;
+
+/// Edge or center of a portal to align.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+#[allow(missing_docs)]
+pub enum AlignAnchor {
+    Min,
+    Center,
+    Max,
+}
+
+/// Returns the coordinate of the center of a portal along `axis`, doubled so
+/// that it is always an integer.
+fn doubled_center(portal: &Portal, axis: Axis) -> i64 {
+    portal.region.min[axis] + portal.region.max[axis]
+}
+
+/// Returns an offset along `axis`.
+fn offset_along(axis: Axis, amount: i64) -> BlockPos {
+    let mut ret = BlockPos::default();
+    ret[axis] = amount;
+    ret
+}
+
+/// Moves `portals` along `axis` so that their `anchor`s line up with the
+/// outermost `anchor` (or the center of the group, for
+/// [`AlignAnchor::Center`]).
+pub fn align(portals: &mut [&mut Portal], axis: Axis, anchor: AlignAnchor, dimension: Dimension) {
+    let mins = portals.iter().map(|p| p.region.min[axis]);
+    let maxs = portals.iter().map(|p| p.region.max[axis]);
+    let (Some(min), Some(max)) = (mins.min(), maxs.max()) else {
+        return; // no portals
+    };
+
+    for portal in portals {
+        let offset = match anchor {
+            AlignAnchor::Min => min - portal.region.min[axis],
+            AlignAnchor::Center => (min + max - doubled_center(portal, axis)).div_euclid(2),
+            AlignAnchor::Max => max - portal.region.max[axis],
+        };
+        portal.translate(offset_along(axis, offset), dimension);
+    }
+}
+
+/// Moves `portals` along `axis` so that their centers are evenly spaced. The
+/// first and last portals along `axis` stay in place.
+pub fn distribute(portals: &mut [&mut Portal], axis: Axis, dimension: Dimension) {
+    portals.sort_by_key(|p| doubled_center(p, axis));
+    let (Some(first), Some(last)) = (portals.first(), portals.last()) else {
+        return; // no portals
+    };
+    let start = doubled_center(first, axis);
+    let end = doubled_center(last, axis);
+
+    let n = portals.len() as i64;
+    for (i, portal) in (0..).zip(portals.iter_mut()) {
+        if n > 1 {
+            let target = start + (end - start) * i / (n - 1);
+            let offset = (target - doubled_center(portal, axis)).div_euclid(2);
+            portal.translate(offset_along(axis, offset), dimension);
+        }
+    }
+}
+
+/// Returns the offset to move `source` by so that `entity` entering it arrives
+/// at `target` and nowhere else. `target` must be in the other dimension.
+///
+/// Returns an error message if there is no such offset nearby, such as when
+/// another portal is closer.
+pub fn link_offset(
+    portals: &WorldPortals,
+    source: &Portal,
+    source_dimension: Dimension,
+    target: &Portal,
+    entity: Entity,
+) -> Result<BlockPos, String> {
+    let target_dimension = source_dimension.other();
+    let source_center = WorldRegion::from(source.region).center();
+    let target_center = WorldRegion::from(target.region)
+        .center()
+        .convert_dimension(target_dimension, source_dimension);
+    let ideal_offset = (target_center - source_center).round();
+
+    let destinations_at = |offset: BlockPos| {
+        let mut moved = source.clone();
+        moved.translate(offset, source_dimension);
+        let region = moved.destination_region(entity, target_dimension)?;
+        Some(portals.portal_destinations(target_dimension, region))
+    };
+    let Some(ideal_destinations) = destinations_at(ideal_offset) else {
+        return Err(format!(
+            "The entity won't fit in {}.",
+            source.display_name(),
+        ));
+    };
+
+    let r = LINK_SEARCH_RADIUS;
+    let nearby_offsets = (-r..=r)
+        .cartesian_product(-r..=r)
+        .sorted_by_key(|&(dx, dz)| dx * dx + dz * dz)
+        .map(|(dx, dz)| ideal_offset + BlockPos { x: dx, y: 0, z: dz });
+    for offset in nearby_offsets {
+        if let Some(destinations) = destinations_at(offset)
+            && !destinations.new_portal
+            && destinations
+                .existing_portals
+                .iter()
+                .map(|p| p.id)
+                .eq([target.id])
+        {
+            return Ok(offset);
+        }
+    }
+
+    let BlockPos { x, y, z } = BlockPos::from(target_center);
+    let mut msg = format!(
+        "There is no position within {r} blocks of {x}, {y}, {z} where {} links only to {}.",
+        source.display_name(),
+        target.display_name(),
+    );
+    let others = ideal_destinations
+        .existing_portals
+        .iter()
+        .filter(|p| p.id != target.id)
+        .map(|p| p.display_name())
+        .join(", ");
+    if !others.is_empty() {
+        msg += &format!(" Closer portals: {others}.");
+    } else if ideal_destinations.new_portal {
+        msg += " The target is too far away, so a new portal would be generated.";
+    }
+    Err(msg)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_align_and_distribute() {
+        let mut portals = [
+            Portal::new_test(([0, 64, 0], [0, 66, 1])),
+            Portal::new_test(([3, 70, 7], [3, 72, 8])),
+            Portal::new_test(([20, 64, 2], [20, 68, 5])),
+        ];
+        let mins = |portals: &[Portal], axis| {
+            portals
+                .iter()
+                .map(|p| p.region.min[axis])
+                .collect::<Vec<_>>()
+        };
+
+        align(
+            &mut portals.each_mut(),
+            Axis::Z,
+            AlignAnchor::Min,
+            Dimension::Overworld,
+        );
+        assert_eq!(mins(&portals, Axis::Z), [0, 0, 0]);
+
+        align(
+            &mut portals.each_mut(),
+            Axis::Y,
+            AlignAnchor::Max,
+            Dimension::Overworld,
+        );
+        assert_eq!(mins(&portals, Axis::Y), [70, 70, 68]);
+
+        distribute(&mut portals.each_mut(), Axis::X, Dimension::Overworld);
+        assert_eq!(mins(&portals, Axis::X), [0, 10, 20]);
+    }
+
+    #[test]
+    fn test_link_offset() {
+        let mut portals = WorldPortals::default();
+        let source = Portal::new_test(([0, 64, 0], [0, 66, 1]));
+        let target = Portal::new_test(([100, 70, 50], [100, 72, 51]));
+        portals.nether.push(target.clone());
+
+        let offset = link_offset(
+            &portals,
+            &source,
+            Dimension::Overworld,
+            &target,
+            Entity::PLAYER,
+        )
+        .expect("no link offset");
+        let mut moved = source.clone();
+        moved.translate(offset, Dimension::Overworld);
+        let region = moved
+            .destination_region(Entity::PLAYER, Dimension::Nether)
+            .expect("entity won't fit");
+        let destinations = portals.portal_destinations(Dimension::Nether, region);
+        assert_eq!(destinations.existing_portals, [&target]);
+        assert!(!destinations.new_portal);
+
+        // An overlapping portal makes it impossible.
+        portals
+            .nether
+            .push(Portal::new_test(([100, 71, 50], [100, 73, 51])));
+        link_offset(
+            &portals,
+            &source,
+            Dimension::Overworld,
+            &target,
+            Entity::PLAYER,
+        )
+        .expect_err("overlapping portal should block the link");
+    }
+}
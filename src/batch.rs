@@ -0,0 +1,87 @@
+//! Evaluation of many arrival positions at once.
+
+use std::fmt::Write;
+
+use itertools::Itertools;
+
+use crate::parse::parse_coordinates;
+use crate::{Dimension, WorldPortals};
+
+/// Header row of the table returned by [`evaluate_positions()`].
+const HEADER: &str = "line,x,y,z,status,destination,destination_x,destination_y,destination_z";
+
+/// Returns a CSV table listing the portal that an entity at each position in
+/// `input` would teleport to.
+///
+/// `input` has one position per line, in any format accepted by
+/// [`parse_coordinates()`], such as `x,y,z`. Blank lines are skipped, and lines
+/// without coordinates are reported as invalid.
+pub fn evaluate_positions(portals: &WorldPortals, dimension: Dimension, input: &str) -> String {
+    let mut out = format!("{HEADER}\n");
+    for (i, line) in input.lines().enumerate() {
+        let line_number = i + 1;
+        if line.trim().is_empty() {
+            continue;
+        }
+        let Some(pos) = parse_coordinates(line) else {
+            let _ = writeln!(out, "{line_number},,,,invalid,,,,");
+            continue;
+        };
+        let destinations = portals.entity_destinations(dimension, pos);
+        let _ = write!(out, "{line_number},{},{},{},", pos.x, pos.y, pos.z);
+        let _ = match destinations.as_slice() {
+            [] => writeln!(out, "new portal,,,,"),
+            [portal] => {
+                let min = portal.region.min;
+                writeln!(
+                    out,
+                    "ok,{},{},{},{}",
+                    csv_field(portal.display_name()),
+                    min.x,
+                    min.y,
+                    min.z,
+                )
+            }
+            _ => {
+                let names = destinations.iter().map(|p| p.display_name()).join("; ");
+                writeln!(out, "ambiguous,{},,,", csv_field(&names))
+            }
+        };
+    }
+    out
+}
+
+/// Quotes a CSV field if necessary.
+fn csv_field(s: &str) -> String {
+    if s.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", s.replace('"', "\"\""))
+    } else {
+        s.to_owned()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Nether, Overworld, Portal};
+
+    #[test]
+    fn test_evaluate_positions() {
+        let mut portals = WorldPortals::default();
+        let mut portal = Portal::new_test(([0, 64, 0], [0, 66, 1]));
+        portal.name = "Hub, north".to_owned();
+        portals[Nether] = vec![portal];
+
+        let input = "0, 64, 0\n\nnot coordinates\n100000 64 0\n";
+        let output = evaluate_positions(&portals, Overworld, input);
+        assert_eq!(
+            output.lines().collect_vec(),
+            [
+                HEADER,
+                "1,0,64,0,ok,\"Hub, north\",0,64,0",
+                "3,,,,invalid,,,,",
+                "4,100000,64,0,new portal,,,,",
+            ],
+        );
+    }
+}
@@ -1,6 +1,6 @@
 use serde::{Deserialize, Serialize};
 
-use crate::{ConvertDimension, Dimension, WorldPos};
+use crate::{Axis, ConvertDimension, Dimension, WorldPos, WorldRegion};
 
 /// Plane of the world to view.
 #[derive(Serialize, Deserialize, Debug, Default, Copy, Clone, PartialEq, Eq, Hash)]
@@ -12,20 +12,45 @@ pub enum Plane {
     ZY,
 }
 impl Plane {
+    /// Returns the factor to multiply Z coordinates by to get the vertical
+    /// plot coordinate in the XZ plane.
+    ///
+    /// By default, -Z (north) is at the top of the XZ plane. If `positive_z_up`
+    /// is `true`, then +Z (south) is at the top instead.
+    pub fn z_sign(positive_z_up: bool) -> f64 {
+        if positive_z_up { 1.0 } else { -1.0 }
+    }
+    /// Returns the horizontal and vertical axes of the plane.
+    pub fn axes(self) -> [Axis; 2] {
+        match self {
+            Plane::XY => [Axis::X, Axis::Y],
+            Plane::XZ => [Axis::X, Axis::Z],
+            Plane::ZY => [Axis::Z, Axis::Y],
+        }
+    }
     /// Converts world coordinates to plot coordinates.
-    pub fn world_to_plot(self, pos: WorldPos) -> egui_plot::PlotPoint {
+    ///
+    /// See [`Plane::z_sign()`] for the meaning of `positive_z_up`.
+    pub fn world_to_plot(self, pos: WorldPos, positive_z_up: bool) -> egui_plot::PlotPoint {
         let [x, y] = match self {
             Plane::XY => [pos.x, pos.y],
-            Plane::XZ => [pos.x, -pos.z],
+            Plane::XZ => [pos.x, pos.z * Self::z_sign(positive_z_up)],
             Plane::ZY => [pos.z, pos.y],
         };
         egui_plot::PlotPoint { x, y }
     }
     /// Converts plot coordinates to world coordinates.
-    pub fn plot_to_world(self, point: egui_plot::PlotPoint, camera: Camera) -> WorldPos {
+    ///
+    /// See [`Plane::z_sign()`] for the meaning of `positive_z_up`.
+    pub fn plot_to_world(
+        self,
+        point: egui_plot::PlotPoint,
+        camera: Camera,
+        positive_z_up: bool,
+    ) -> WorldPos {
         let [x, y, z] = match self {
             Plane::XY => [point.x, point.y, camera.pos.z],
-            Plane::XZ => [point.x, camera.pos.y, -point.y],
+            Plane::XZ => [point.x, camera.pos.y, point.y * Self::z_sign(positive_z_up)],
             Plane::ZY => [camera.pos.x, point.y, point.x],
         };
         WorldPos { x, y, z }
@@ -66,6 +91,28 @@ impl Camera {
         *self = Self::default();
     }
 
+    /// Resets the camera to view `region` in `dimension`, or the origin of
+    /// `dimension` if `region` is `None`.
+    pub fn reset_to_region(&mut self, dimension: Dimension, region: Option<WorldRegion>) {
+        /// Fraction of the viewport to leave around the region.
+        const MARGIN: f64 = 0.2;
+        /// Smallest viewport size, so that a single portal is not too zoomed in.
+        const MIN_SIZE: f64 = 64.0;
+
+        self.reset();
+        self.set_dimension(dimension);
+        if let Some(region) = region {
+            self.pos = region.center();
+            let span = (region.max.x - region.min.x)
+                .max(region.max.y - region.min.y)
+                .max(region.max.z - region.min.z);
+            // The viewport is measured in overworld coordinates.
+            let size = (span * dimension.scale() * (1.0 + MARGIN)).max(MIN_SIZE);
+            self.width = size;
+            self.height = size;
+        }
+    }
+
     /// Returns the position of the camera in the given dimension.
     pub fn pos_in(self, dimension: Dimension) -> WorldPos {
         self.pos.convert_dimension(self.dimension, dimension)
@@ -0,0 +1,118 @@
+//! Printable checklist for building planned portals in-game.
+
+use std::collections::{HashMap, HashSet};
+use std::fmt::Write;
+
+use crate::{Dimension, Nether, Overworld, Portal, PortalId, PortalLinkResult, World};
+
+/// Returns a plain-text checklist of all portals that have not been built yet,
+/// in the recommended build order.
+///
+/// Walking through a portal whose destination does not exist yet generates a
+/// new portal, so each portal is listed after the portals it links to whenever
+/// possible.
+pub fn build_checklist(
+    world: &World,
+    links: &HashMap<PortalId, (PortalLinkResult, Vec<PortalId>)>,
+) -> String {
+    let portals_by_id: HashMap<PortalId, &Portal> =
+        world.portals.iter().map(|p| (p.id, p)).collect();
+
+    let mut existing: HashSet<PortalId> = portals_by_id
+        .values()
+        .filter(|p| p.built)
+        .map(|p| p.id)
+        .collect();
+    let mut remaining: Vec<(Dimension, &Portal)> = [Overworld, Nether]
+        .into_iter()
+        .flat_map(|dim| world.portals[dim].iter().map(move |p| (dim, p)))
+        .filter(|(_, p)| !p.built)
+        .collect();
+
+    let outgoing = |id: &PortalId| match links.get(id) {
+        Some((PortalLinkResult::Portals { ids, new_portal }, _)) => (ids.as_slice(), *new_portal),
+        _ => (&[][..], false),
+    };
+
+    let mut out = String::new();
+    if remaining.is_empty() {
+        out += "All portals have been built.\n";
+        return out;
+    }
+    let _ = writeln!(out, "Build checklist ({} portals)", remaining.len());
+
+    let mut step = 0;
+    while !remaining.is_empty() {
+        step += 1;
+
+        // Prefer a portal whose destinations all exist already.
+        let is_safe = |p: &Portal| {
+            let (ids, new_portal) = outgoing(&p.id);
+            !new_portal && !ids.is_empty() && ids.iter().all(|id| existing.contains(id))
+        };
+        let i = remaining.iter().position(|(_, p)| is_safe(p));
+        let (dimension, portal) = remaining.remove(i.unwrap_or(0));
+        existing.insert(portal.id);
+
+        let names = |ids: &[PortalId]| {
+            ids.iter()
+                .map(|id| {
+                    portals_by_id
+                        .get(id)
+                        .map_or("<unknown>", |p| p.display_name())
+                })
+                .collect::<Vec<_>>()
+                .join(", ")
+        };
+
+        let min = portal.region.min;
+        let max = portal.region.max;
+        let _ = writeln!(out);
+        let _ = writeln!(
+            out,
+            "{step}. [ ] {name} ({dimension})",
+            name = portal.display_name(),
+        );
+        let _ = writeln!(
+            out,
+            "   Portal blocks from {}, {}, {} to {}, {}, {}",
+            min.x, min.y, min.z, max.x, max.y, max.z,
+        );
+        let _ = writeln!(
+            out,
+            "   Entered from {}, {} wide × {} tall",
+            portal.axis.entry_directions(),
+            portal.width(),
+            portal.height(),
+        );
+
+        match links.get(&portal.id) {
+            Some((PortalLinkResult::EntityWontFit(reason), _)) => {
+                let _ = writeln!(out, "   {reason}");
+            }
+            Some((PortalLinkResult::Portals { ids, new_portal }, _)) => {
+                if !ids.is_empty() {
+                    let _ = writeln!(out, "   Links to: {}", names(ids));
+                }
+                if *new_portal {
+                    let _ = writeln!(out, "   Generates new portal");
+                }
+                let not_yet_built = ids
+                    .iter()
+                    .copied()
+                    .filter(|id| !existing.contains(id))
+                    .collect::<Vec<_>>();
+                if !not_yet_built.is_empty() {
+                    let _ = writeln!(
+                        out,
+                        "   Until {} is built, entering this portal will generate a new one",
+                        names(&not_yet_built),
+                    );
+                }
+            }
+            None => (),
+        }
+    }
+
+    out
+}
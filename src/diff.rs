@@ -0,0 +1,102 @@
+//! Comparison between two versions of a world.
+
+use std::collections::{HashMap, HashSet};
+
+use crate::world::ListPerDimension;
+use crate::{BlockRegion, Nether, Overworld, Portal, PortalId, World};
+
+/// Differences between the portals of the current world and another version of
+/// it.
+#[derive(Debug, Default, Clone)]
+pub struct WorldDiff {
+    /// Portals in the other world with no match in the current world.
+    pub added: ListPerDimension<Portal>,
+    /// Portals in the current world with no match in the other world.
+    pub removed: HashSet<PortalId>,
+    /// Portals in the current world that are somewhere else in the other
+    /// world, along with their region in the other world.
+    pub moved: HashMap<PortalId, BlockRegion>,
+}
+
+impl WorldDiff {
+    /// Compares the portals in `current` with those in `other`.
+    ///
+    /// Portals are matched first by position and then by name, so a portal
+    /// that has been renamed in place or moved without being renamed is not
+    /// reported as both added and removed.
+    pub fn new(current: &World, other: &World) -> Self {
+        let mut ret = Self::default();
+        for dimension in [Overworld, Nether] {
+            let mut unmatched_other = other.portals[dimension].iter().collect::<Vec<_>>();
+            let mut unmatched_current = vec![];
+            for portal in &current.portals[dimension] {
+                match unmatched_other
+                    .iter()
+                    .position(|p| p.region == portal.region)
+                {
+                    Some(i) => {
+                        unmatched_other.remove(i);
+                    }
+                    None => unmatched_current.push(portal),
+                }
+            }
+            for portal in unmatched_current {
+                match unmatched_other
+                    .iter()
+                    .position(|p| !portal.name.is_empty() && p.name == portal.name)
+                {
+                    Some(i) => {
+                        ret.moved
+                            .insert(portal.id, unmatched_other.remove(i).region);
+                    }
+                    None => {
+                        ret.removed.insert(portal.id);
+                    }
+                }
+            }
+            ret.added[dimension] = unmatched_other.into_iter().cloned().collect();
+        }
+        ret
+    }
+
+    /// Returns a short summary, such as "2 added, 0 removed, 1 moved".
+    pub fn summary(&self) -> String {
+        format!(
+            "{} added, {} removed, {} moved",
+            self.added.overworld.len() + self.added.nether.len(),
+            self.removed.len(),
+            self.moved.len(),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::BlockPos;
+
+    #[test]
+    fn test_world_diff() {
+        let mut a = Portal::new_test(([0, 64, 0], [0, 66, 1]));
+        a.name = "Home".to_owned();
+        let b = Portal::new_test(([8, 64, 0], [8, 66, 1]));
+        let c = Portal::new_test(([16, 64, 0], [16, 66, 1]));
+        let mut current = World::default();
+        current.portals.overworld = vec![a.clone(), b.clone()];
+
+        let mut moved_a = a.clone();
+        moved_a.translate(BlockPos { x: 0, y: 0, z: 32 }, Overworld);
+        let mut other = World::default();
+        other.portals.overworld = vec![c.clone(), moved_a.clone(), b.clone()];
+        other.portals.overworld[2].name = "Renamed".to_owned();
+
+        let diff = WorldDiff::new(&current, &other);
+        assert_eq!(diff.added.overworld, [c]);
+        assert!(diff.removed.is_empty());
+        assert_eq!(diff.moved, HashMap::from([(a.id, moved_a.region)]));
+
+        let diff = WorldDiff::new(&other, &current);
+        assert!(diff.added.overworld.is_empty());
+        assert_eq!(diff.removed, HashSet::from([other.portals.overworld[0].id]));
+    }
+}
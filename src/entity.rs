@@ -29,6 +29,12 @@ impl fmt::Display for Entity {
 }
 
 impl Entity {
+    /// Returns the distance from the entity's position to the side of its
+    /// hitbox along the X and Z axes.
+    pub fn half_width(self) -> f64 {
+        self.width / 2.0
+    }
+
     /// Player entity
     pub const PLAYER: Self = Entity {
         width: 0.6,
@@ -59,4 +65,12 @@ impl Entity {
         height: 0.25,
         is_projectile: false,
     };
+    /// Point with no hitbox, which collides with exactly the portal blocks.
+    /// This is useful for reasoning about links between portal regions
+    /// without any entity-specific effects.
+    pub const POINT: Self = Entity {
+        width: 0.0,
+        height: 0.0,
+        is_projectile: false,
+    };
 }
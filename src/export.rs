@@ -0,0 +1,313 @@
+//! Export of the portal network to other file formats.
+
+use std::collections::HashMap;
+use std::fmt::Write;
+
+use itertools::Itertools;
+
+use crate::{
+    BlockPos, BlockRegion, ConvertDimension, Nether, Overworld, PortalAxis, PortalId,
+    PortalLinkResult, World, WorldPos,
+};
+
+/// Returns the link graph as a `GraphViz` DOT file.
+///
+/// Each portal is a node, grouped into a cluster for each dimension and filled
+/// with the portal's color. Each link is an edge, which is dashed if the link
+/// is ambiguous.
+pub fn to_dot(
+    world: &World,
+    links: &HashMap<PortalId, (PortalLinkResult, Vec<PortalId>)>,
+) -> String {
+    let node_names: HashMap<PortalId, String> = world
+        .portals
+        .iter()
+        .enumerate()
+        .map(|(i, p)| (p.id, format!("p{}", i + 1)))
+        .collect();
+
+    let mut out = String::from("digraph portals {\n");
+    out += "    node [shape=box, style=filled];\n";
+    for dimension in [Overworld, Nether] {
+        let _ = writeln!(out, "    subgraph cluster_{dimension:?} {{");
+        let _ = writeln!(out, "        label={};", quoted(&dimension.to_string()));
+        for portal in &world.portals[dimension] {
+            let [r, g, b] = portal.color;
+            let font_color = if (r as u32 + g as u32 + b as u32) < 384 {
+                "white"
+            } else {
+                "black"
+            };
+            let _ = writeln!(
+                out,
+                "        {} [label={}, fillcolor=\"#{r:02x}{g:02x}{b:02x}\", fontcolor={font_color}];",
+                node_names[&portal.id],
+                quoted(portal.display_name()),
+            );
+        }
+        out += "    }\n";
+    }
+    for portal in world.portals.iter() {
+        let Some((PortalLinkResult::Portals { ids, .. }, _)) = links.get(&portal.id) else {
+            continue;
+        };
+        let style = if ids.len() > 1 { " [style=dashed]" } else { "" };
+        for id in ids {
+            if let Some(target) = node_names.get(id) {
+                let _ = writeln!(out, "    {} -> {target}{style};", node_names[&portal.id]);
+            }
+        }
+    }
+    out += "}\n";
+    out
+}
+
+/// Returns a Markdown table listing each portal with its coordinates in both
+/// dimensions and the portals it links to and from.
+///
+/// Coordinates are those of the minimum corner of the portal.
+pub fn to_markdown_table(
+    world: &World,
+    links: &HashMap<PortalId, (PortalLinkResult, Vec<PortalId>)>,
+) -> String {
+    let names: HashMap<PortalId, &str> = world
+        .portals
+        .iter()
+        .map(|p| (p.id, p.display_name()))
+        .collect();
+    let name_list = |ids: &[PortalId]| {
+        ids.iter()
+            .filter_map(|id| names.get(id))
+            .map(|name| markdown_cell(name))
+            .join(", ")
+    };
+    let coords = |BlockPos { x, y, z }: BlockPos| format!("{x}, {y}, {z}");
+
+    let mut out = String::from(
+        "| Portal | Dimension | Overworld | Nether | Links to | Links from |\n\
+         | --- | --- | --- | --- | --- | --- |\n",
+    );
+    for dimension in [Overworld, Nether] {
+        for portal in &world.portals[dimension] {
+            let min = portal.region.min;
+            let converted = WorldPos::from(min)
+                .convert_dimension(dimension, dimension.other())
+                .floor();
+            let (overworld, nether) = match dimension {
+                Overworld => (min, converted),
+                Nether => (converted, min),
+            };
+            let (links_to, links_from) = match links.get(&portal.id) {
+                Some((PortalLinkResult::Portals { ids, new_portal }, incoming)) => {
+                    let mut links_to = name_list(ids);
+                    if *new_portal {
+                        if !links_to.is_empty() {
+                            links_to += ", ";
+                        }
+                        links_to += "new portal";
+                    }
+                    (links_to, name_list(incoming))
+                }
+                Some((PortalLinkResult::EntityWontFit(reason), incoming)) => {
+                    (reason.to_string(), name_list(incoming))
+                }
+                None => (String::new(), String::new()),
+            };
+            let _ = writeln!(
+                out,
+                "| {} | {dimension} | {} | {} | {links_to} | {links_from} |",
+                markdown_cell(portal.display_name()),
+                coords(overworld),
+                coords(nether),
+            );
+        }
+    }
+    out
+}
+
+/// Returns a Minecraft function that builds every portal, with an obsidian
+/// frame around its region filled with lit portal blocks.
+///
+/// Each command uses `execute in` so that the function can be run from either
+/// dimension.
+pub fn to_mcfunction(world: &World) -> String {
+    let mut out = String::from("# Generated by Portal Planner\n");
+    for dimension in [Overworld, Nether] {
+        let dimension_id = match dimension {
+            Overworld => "minecraft:overworld",
+            Nether => "minecraft:the_nether",
+        };
+        for portal in &world.portals[dimension] {
+            let region = portal.region;
+            let mut frame = region;
+            frame.min.y -= 1;
+            frame.max.y += 1;
+            // The game's axis is the one along the portal's width, which is
+            // the opposite of `PortalAxis`.
+            let game_axis = match portal.axis {
+                PortalAxis::X => {
+                    frame.min.z -= 1;
+                    frame.max.z += 1;
+                    "z"
+                }
+                PortalAxis::Z => {
+                    frame.min.x -= 1;
+                    frame.max.x += 1;
+                    "x"
+                }
+            };
+            let fill = |region: BlockRegion, block: &str| {
+                let BlockRegion { min, max } = region;
+                format!(
+                    "execute in {dimension_id} run fill {} {} {} {} {} {} {block}",
+                    min.x, min.y, min.z, max.x, max.y, max.z,
+                )
+            };
+            let _ = writeln!(
+                out,
+                "\n# {} ({dimension})",
+                portal.display_name().replace('\n', " "),
+            );
+            let _ = writeln!(out, "{}", fill(frame, "minecraft:obsidian"));
+            let _ = writeln!(
+                out,
+                "{}",
+                fill(
+                    region,
+                    &format!("minecraft:nether_portal[axis={game_axis}]")
+                ),
+            );
+        }
+    }
+    out
+}
+
+/// Escapes text for use in a Markdown table cell.
+fn markdown_cell(s: &str) -> String {
+    s.replace('|', "\\|").replace('\n', " ")
+}
+
+/// Returns a quoted DOT string.
+fn quoted(s: &str) -> String {
+    format!("\"{}\"", s.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Portal;
+
+    #[test]
+    fn test_to_dot() {
+        let mut world = World::default();
+        let mut a = Portal::new_test(([0, 64, 0], [0, 66, 1]));
+        a.name = "Base \"A\"".to_owned();
+        a.color = [255, 255, 255];
+        let b = Portal::new_test(([0, 64, 0], [0, 66, 1]));
+        world.portals.overworld = vec![a.clone()];
+        world.portals.nether = vec![b.clone()];
+        let links = HashMap::from([
+            (
+                a.id,
+                (
+                    PortalLinkResult::Portals {
+                        ids: vec![b.id],
+                        new_portal: false,
+                    },
+                    vec![b.id],
+                ),
+            ),
+            (
+                b.id,
+                (
+                    PortalLinkResult::Portals {
+                        ids: vec![a.id],
+                        new_portal: false,
+                    },
+                    vec![a.id],
+                ),
+            ),
+        ]);
+
+        let dot = to_dot(&world, &links);
+        assert!(dot.starts_with("digraph portals {\n"));
+        assert!(dot.contains("subgraph cluster_Overworld {"));
+        assert!(
+            dot.contains("p1 [label=\"Base \\\"A\\\"\", fillcolor=\"#ffffff\", fontcolor=black];")
+        );
+        assert!(dot.contains("    p1 -> p2;\n"));
+        assert!(dot.contains("    p2 -> p1;\n"));
+    }
+
+    #[test]
+    fn test_to_markdown_table() {
+        let mut world = World::default();
+        let mut a = Portal::new_test(([80, 64, -16], [80, 66, -15]));
+        a.name = "Spawn | Hub".to_owned();
+        let mut b = Portal::new_test(([10, 64, -2], [10, 66, -1]));
+        b.name = "Hub".to_owned();
+        world.portals.overworld = vec![a.clone()];
+        world.portals.nether = vec![b.clone()];
+        let links = HashMap::from([
+            (
+                a.id,
+                (
+                    PortalLinkResult::Portals {
+                        ids: vec![b.id],
+                        new_portal: false,
+                    },
+                    vec![],
+                ),
+            ),
+            (
+                b.id,
+                (
+                    PortalLinkResult::Portals {
+                        ids: vec![],
+                        new_portal: true,
+                    },
+                    vec![a.id],
+                ),
+            ),
+        ]);
+
+        let table = to_markdown_table(&world, &links);
+        assert_eq!(
+            table.lines().collect_vec(),
+            [
+                "| Portal | Dimension | Overworld | Nether | Links to | Links from |",
+                "| --- | --- | --- | --- | --- | --- |",
+                "| Spawn \\| Hub | Overworld | 80, 64, -16 | 10, 64, -2 | Hub |  |",
+                "| Hub | Nether | 80, 64, -16 | 10, 64, -2 | new portal | Spawn \\| Hub |",
+            ],
+        );
+    }
+
+    #[test]
+    fn test_to_mcfunction() {
+        let mut world = World::default();
+        let mut a = Portal::new_test(([80, 64, -16], [80, 66, -15]));
+        a.name = "Spawn".to_owned();
+        a.axis = PortalAxis::X;
+        let mut b = Portal::new_test(([10, 64, -2], [11, 66, -2]));
+        b.axis = PortalAxis::Z;
+        world.portals.overworld = vec![a];
+        world.portals.nether = vec![b.clone()];
+
+        let function = to_mcfunction(&world);
+        assert_eq!(
+            function.lines().collect_vec(),
+            [
+                "# Generated by Portal Planner",
+                "",
+                "# Spawn (Overworld)",
+                "execute in minecraft:overworld run fill 80 63 -17 80 67 -14 minecraft:obsidian",
+                "execute in minecraft:overworld run fill 80 64 -16 80 66 -15 minecraft:nether_portal[axis=z]",
+                "",
+                &format!("# {} (Nether)", b.display_name()),
+                "execute in minecraft:the_nether run fill 9 63 -2 12 67 -2 minecraft:obsidian",
+                "execute in minecraft:the_nether run fill 10 64 -2 11 66 -2 minecraft:nether_portal[axis=x]",
+            ],
+        );
+    }
+}
@@ -0,0 +1,566 @@
+//! Analysis of the links between portals.
+
+use std::collections::{HashMap, VecDeque};
+
+use itertools::Itertools;
+use serde::Serialize;
+
+use crate::{
+    BlockPos, BlockRegion, ConvertDimension, Dimension, Entity, EntityWontFit, Nether,
+    NewPortalPlacement, Overworld, PerDimension, Portal, PortalId, PortalLinkResult, World,
+    WorldPortals, WorldRegion,
+};
+
+/// Maximum number of portals in a loop found by [`find_loop()`].
+pub const MAX_LOOP_LENGTH: usize = 8;
+
+/// Returns the shortest loop of links that starts and ends at `start`, not
+/// counting a pair of portals that link to each other.
+///
+/// The returned list starts with `start` and does not repeat it at the end.
+pub fn find_loop(
+    links: &HashMap<PortalId, (PortalLinkResult, Vec<PortalId>)>,
+    start: PortalId,
+) -> Option<Vec<PortalId>> {
+    let outgoing = |id: PortalId| match links.get(&id) {
+        Some((PortalLinkResult::Portals { ids, .. }, _)) => ids.as_slice(),
+        _ => &[],
+    };
+
+    // Breadth-first search, recording the previous portal on the shortest path
+    // to each portal.
+    let mut previous: HashMap<PortalId, PortalId> = HashMap::new();
+    let mut queue = VecDeque::from([(start, 1)]);
+    while let Some((id, length)) = queue.pop_front() {
+        for &next in outgoing(id) {
+            if next == start {
+                if length > 2 {
+                    let mut ret = vec![id];
+                    while let Some(&prev) = previous.get(ret.last()?) {
+                        ret.push(prev);
+                    }
+                    ret.reverse();
+                    return Some(ret);
+                }
+            } else if length < MAX_LOOP_LENGTH && !previous.contains_key(&next) {
+                previous.insert(next, id);
+                queue.push_back((next, length + 1));
+            }
+        }
+    }
+    None
+}
+
+/// Returns whether the portal with ID `source` links only to `destination`.
+pub fn is_intended_link_met(
+    links: &HashMap<PortalId, (PortalLinkResult, Vec<PortalId>)>,
+    source: PortalId,
+    destination: PortalId,
+) -> bool {
+    matches!(
+        links.get(&source),
+        Some((PortalLinkResult::Portals { ids, new_portal: false }, _)) if *ids == [destination]
+    )
+}
+
+/// Returns the intended links in `world` that are not met, as pairs of source
+/// and intended destination, skipping any whose portals no longer exist.
+pub fn unmet_intended_links(
+    world: &World,
+    links: &HashMap<PortalId, (PortalLinkResult, Vec<PortalId>)>,
+) -> Vec<(PortalId, PortalId)> {
+    let exists = |id: PortalId| world.portals.get(id).is_some();
+    world
+        .intended_links
+        .iter()
+        .map(|(&source, &destination)| (source, destination))
+        .filter(|&(source, destination)| exists(source) && exists(destination))
+        .filter(|&(source, destination)| !is_intended_link_met(links, source, destination))
+        .sorted()
+        .collect()
+}
+
+/// Counts summarizing the health of a portal network.
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq)]
+pub struct NetworkStats {
+    /// Number of portals in each dimension.
+    pub portals: PerDimension<usize>,
+    /// Number of test points in each dimension.
+    pub test_points: PerDimension<usize>,
+    /// Number of links from one portal to another.
+    pub links: usize,
+    /// Number of portals that may link to more than one portal.
+    pub ambiguous: usize,
+    /// Number of portals that no portal links to.
+    pub orphans: usize,
+    /// Number of portals that may generate a new portal.
+    pub new_portals: usize,
+    /// Number of portals with no existing portal in range, which always
+    /// generate a new portal.
+    pub isolated: usize,
+    /// Number of portals that the entity won't fit in.
+    pub entity_wont_fit: usize,
+    /// Number of portals that don't link to their intended destination.
+    pub unmet_intended_links: usize,
+}
+
+impl NetworkStats {
+    /// Computes statistics for `world` using links calculated by the app.
+    pub fn new(
+        world: &World,
+        links: &HashMap<PortalId, (PortalLinkResult, Vec<PortalId>)>,
+    ) -> Self {
+        let mut ret = Self::default();
+        for dimension in [Overworld, Nether] {
+            ret.portals[dimension] = world.portals[dimension].len();
+            ret.test_points[dimension] = world.test_points[dimension].len();
+        }
+        let all_portals = world.portals.iter();
+        for (outgoing, incoming) in all_portals.filter_map(|p| links.get(&p.id)) {
+            match outgoing {
+                PortalLinkResult::EntityWontFit(_) => ret.entity_wont_fit += 1,
+                PortalLinkResult::Portals { ids, new_portal } => {
+                    ret.links += ids.len();
+                    ret.ambiguous += (ids.len() > 1) as usize;
+                    ret.new_portals += *new_portal as usize;
+                }
+            }
+            ret.isolated += outgoing.is_isolated() as usize;
+            ret.orphans += incoming.is_empty() as usize;
+        }
+        ret.unmet_intended_links = unmet_intended_links(world, links).len();
+        ret
+    }
+}
+
+/// Every link in a world for one entity, which can be serialized for use by
+/// other tools.
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct LinkReport {
+    /// Entity that the links are for.
+    pub entity: Entity,
+    /// Overworld portals followed by nether portals, in the same order as in
+    /// the world.
+    pub portals: Vec<PortalReport>,
+}
+
+/// Links to and from one portal in a [`LinkReport`].
+///
+/// Portals are referred to by their index in [`LinkReport::portals`], because
+/// portal IDs are only unique within a session.
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct PortalReport {
+    /// ID of the portal in this session.
+    #[serde(skip)]
+    pub id: PortalId,
+    /// Human-friendly name of the portal.
+    pub name: String,
+    /// Dimension containing the portal.
+    pub dimension: Dimension,
+    /// Region filled with portal blocks.
+    pub region: BlockRegion,
+    /// Reason that the entity won't fit in the portal, if it doesn't.
+    pub entity_wont_fit: Option<EntityWontFit>,
+    /// Portals that the entity may arrive at, from closest to farthest.
+    pub links_to: Vec<usize>,
+    /// Portals that link to this one.
+    pub linked_from: Vec<usize>,
+    /// Whether a new portal may be generated.
+    pub new_portal: bool,
+    /// Block near which a new portal is predicted, if one may be generated.
+    pub new_portal_block: Option<BlockPos>,
+}
+
+impl LinkReport {
+    /// Computes every link in `world` for `entity`.
+    ///
+    /// `step_limit` is passed to
+    /// [`WorldPortals::portal_destinations_with_step_limit()`], and
+    /// `placement` is used to predict where new portals are generated.
+    pub fn new(
+        world: &World,
+        entity: Entity,
+        step_limit: usize,
+        placement: NewPortalPlacement,
+    ) -> Self {
+        let indices: HashMap<PortalId, usize> = world
+            .portals
+            .iter()
+            .enumerate()
+            .map(|(i, p)| (p.id, i))
+            .collect();
+        let results: HashMap<PortalId, PortalLinkResult> =
+            outgoing_link_results(&world.portals, entity, step_limit)
+                .into_iter()
+                .collect();
+
+        let mut portals = world
+            .portals
+            .iter_with_dimension()
+            .map(|(dimension, portal)| {
+                let mut report = PortalReport {
+                    id: portal.id,
+                    name: portal.name.clone(),
+                    dimension,
+                    region: portal.region,
+                    entity_wont_fit: None,
+                    links_to: vec![],
+                    linked_from: vec![],
+                    new_portal: false,
+                    new_portal_block: None,
+                };
+                match &results[&portal.id] {
+                    PortalLinkResult::EntityWontFit(reason) => {
+                        report.entity_wont_fit = Some(*reason);
+                    }
+                    PortalLinkResult::Portals { ids, new_portal } => {
+                        report.links_to = ids
+                            .iter()
+                            .filter_map(|id| indices.get(id))
+                            .copied()
+                            .collect();
+                        report.new_portal = *new_portal;
+                        if *new_portal {
+                            report.new_portal_block =
+                                portal.new_portal_block(entity, dimension.other(), placement);
+                        }
+                    }
+                }
+                report
+            })
+            .collect_vec();
+
+        for i in 0..portals.len() {
+            for j in portals[i].links_to.clone() {
+                portals[j].linked_from.push(i);
+            }
+        }
+
+        LinkReport { entity, portals }
+    }
+
+    /// Returns where each portal links and which portals link to it, keyed by
+    /// portal ID.
+    pub(crate) fn to_link_map(&self) -> HashMap<PortalId, (PortalLinkResult, Vec<PortalId>)> {
+        let ids = |indices: &[usize]| indices.iter().map(|&i| self.portals[i].id).collect_vec();
+        self.portals
+            .iter()
+            .map(|p| {
+                let outgoing = match p.entity_wont_fit {
+                    Some(reason) => PortalLinkResult::EntityWontFit(reason),
+                    None => PortalLinkResult::Portals {
+                        ids: ids(&p.links_to),
+                        new_portal: p.new_portal,
+                    },
+                };
+                (p.id, (outgoing, ids(&p.linked_from)))
+            })
+            .collect()
+    }
+}
+
+/// Step-by-step explanation of where a portal links.
+#[derive(Debug, Clone, PartialEq)]
+pub struct LinkExplanation {
+    /// Region where the entity can collide with the portal.
+    pub collision_region: WorldRegion,
+    /// Region where the entity may try to arrive, in the destination
+    /// dimension.
+    pub destination_region: BlockRegion,
+    /// Portals in the destination dimension, with the ones that the portal
+    /// links to first and the rest sorted by distance.
+    pub candidates: Vec<LinkCandidate>,
+    /// Whether a new portal may be generated.
+    pub new_portal: bool,
+}
+
+/// Portal considered as a destination by [`explain_link()`].
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct LinkCandidate {
+    /// Portal ID.
+    pub id: PortalId,
+    /// Minimum distance from the destination region to the portal.
+    pub min_distance: f64,
+    /// Maximum distance from the destination region to the portal.
+    pub max_distance: f64,
+    /// Outcome for the portal.
+    pub status: LinkCandidateStatus,
+}
+
+/// Outcome for a portal considered as a destination by [`explain_link()`].
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum LinkCandidateStatus {
+    /// The portal is reachable from some point in the destination region.
+    Reachable,
+    /// The portal is outside the search range for every point in the
+    /// destination region.
+    OutOfRange,
+    /// Another portal that is always in range is always closer.
+    AlwaysFarther,
+    /// The portal is in range, but some other portal is closer at every point
+    /// in the destination region.
+    NeverClosest,
+}
+
+/// Returns where `entity` entering `portal` links to.
+///
+/// `step_limit` is passed to
+/// [`WorldPortals::portal_destinations_with_step_limit()`].
+pub fn link_result(
+    portals: &WorldPortals,
+    portal: &Portal,
+    portal_dimension: Dimension,
+    entity: Entity,
+    step_limit: usize,
+) -> PortalLinkResult {
+    match portal.links_to_with_step_limit(portals, portal_dimension, entity, step_limit) {
+        Ok(destinations) => PortalLinkResult::Portals {
+            ids: destinations.existing_portals.iter().map(|p| p.id).collect(),
+            new_portal: destinations.new_portal,
+        },
+        Err(reason) => PortalLinkResult::EntityWontFit(reason),
+    }
+}
+
+/// Returns where `entity` entering each portal links to.
+///
+/// Each portal is independent, so on native they are calculated in parallel.
+pub fn outgoing_link_results(
+    portals: &WorldPortals,
+    entity: Entity,
+    step_limit: usize,
+) -> Vec<(PortalId, PortalLinkResult)> {
+    let all_portals = portals.iter_with_dimension().collect_vec();
+    let calculate = |&(dimension, portal): &(Dimension, &Portal)| {
+        let result = link_result(portals, portal, dimension, entity, step_limit);
+        (portal.id, result)
+    };
+
+    #[cfg(not(target_arch = "wasm32"))]
+    {
+        use rayon::prelude::*;
+        all_portals.par_iter().map(calculate).collect()
+    }
+    #[cfg(target_arch = "wasm32")]
+    all_portals.iter().map(calculate).collect()
+}
+
+/// Returns a step-by-step explanation of where `entity` entering `portal`
+/// links to, or `None` if the entity won't fit.
+pub fn explain_link(
+    portals: &WorldPortals,
+    portal: &Portal,
+    portal_dimension: Dimension,
+    entity: Entity,
+) -> Option<LinkExplanation> {
+    let destination_dimension = portal_dimension.other();
+    let collision_region = portal.entity_collision_region(entity)?;
+    let destination_region = collision_region
+        .convert_dimension(portal_dimension, destination_dimension)
+        .block_region_containing();
+    let destinations = portals.portal_destinations(destination_dimension, destination_region);
+
+    let candidates = &portals[destination_dimension];
+    let smallest_max_distance_sq = candidates
+        .iter()
+        .filter(|p| p.is_always_in_range_of_region(destination_region, destination_dimension))
+        .map(|p| destination_region.max_euclidean_distance_sq_to(p.region))
+        .min()
+        .unwrap_or(i64::MAX);
+
+    let candidates = candidates
+        .iter()
+        .map(|p| {
+            let min_distance_sq = destination_region.min_euclidean_distance_sq_to(p.region);
+            let max_distance_sq = destination_region.max_euclidean_distance_sq_to(p.region);
+            let status = if destinations.existing_portals.iter().any(|d| d.id == p.id) {
+                LinkCandidateStatus::Reachable
+            } else if !p.is_in_range_of_region(destination_region, destination_dimension) {
+                LinkCandidateStatus::OutOfRange
+            } else if min_distance_sq > smallest_max_distance_sq {
+                LinkCandidateStatus::AlwaysFarther
+            } else {
+                LinkCandidateStatus::NeverClosest
+            };
+            LinkCandidate {
+                id: p.id,
+                min_distance: (min_distance_sq as f64).sqrt(),
+                max_distance: (max_distance_sq as f64).sqrt(),
+                status,
+            }
+        })
+        .sorted_by(|a, b| {
+            let a_reachable = a.status == LinkCandidateStatus::Reachable;
+            let b_reachable = b.status == LinkCandidateStatus::Reachable;
+            b_reachable
+                .cmp(&a_reachable)
+                .then(a.min_distance.total_cmp(&b.min_distance))
+        })
+        .collect();
+
+    Some(LinkExplanation {
+        collision_region,
+        destination_region,
+        candidates,
+        new_portal: destinations.new_portal,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_link_report() {
+        let world = World::example();
+        let report = world.analyze(Entity::PLAYER);
+        assert_eq!(report.portals.len(), 5);
+        let index = |dimension, name| {
+            (report.portals.iter())
+                .position(|p| p.dimension == dimension && p.name == name)
+                .expect("missing portal")
+        };
+        let overworld_spawn = index(Overworld, "Spawn");
+        let nether_spawn = index(Nether, "Spawn");
+        assert_eq!(report.portals[overworld_spawn].links_to, vec![nether_spawn]);
+        assert_eq!(
+            report.portals[nether_spawn].linked_from,
+            vec![overworld_spawn]
+        );
+
+        let temple = &report.portals[index(Overworld, "Desert Temple")];
+        assert!(temple.links_to.is_empty());
+        assert!(temple.new_portal);
+        assert!(temple.new_portal_block.is_some());
+
+        let links = report.to_link_map();
+        for (id, outgoing) in outgoing_link_results(&world.portals, Entity::PLAYER, usize::MAX) {
+            assert_eq!(links[&id].0, outgoing);
+        }
+
+        let json = serde_json::to_value(&report).expect("serialization failed");
+        assert_eq!(
+            json["portals"][overworld_spawn]["links_to"][0],
+            nether_spawn
+        );
+    }
+
+    #[test]
+    fn test_network_stats() {
+        let world = World::example();
+        let links = world.analyze(Entity::PLAYER).to_link_map();
+        let stats = NetworkStats::new(&world, &links);
+        assert_eq!(stats.links, 4);
+        assert_eq!(stats.new_portals, 1);
+        assert_eq!(stats.isolated, 1);
+        assert_eq!(stats.ambiguous, 0);
+        assert_eq!(stats.orphans, 1);
+    }
+
+    #[test]
+    fn test_example_world_links() {
+        let world = World::example();
+        let portals = &world.portals;
+        for (dimension, portal) in portals.iter_with_dimension() {
+            let result = link_result(portals, portal, dimension, Entity::PLAYER, usize::MAX);
+            let expected = match &*portal.name {
+                "Desert Temple" => PortalLinkResult::Portals {
+                    ids: vec![],
+                    new_portal: true,
+                },
+                name => {
+                    let other = portals[dimension.other()]
+                        .iter()
+                        .find(|p| p.name == name)
+                        .expect("missing partner portal");
+                    PortalLinkResult::Portals {
+                        ids: vec![other.id],
+                        new_portal: false,
+                    }
+                }
+            };
+            assert_eq!(result, expected, "{}", portal.name);
+        }
+    }
+
+    #[test]
+    fn test_outgoing_link_results() {
+        let portals = WorldPortals {
+            overworld: vec![Portal::new_test(([80, 64, 0], [80, 66, 1]))],
+            nether: vec![
+                Portal::new_test(([10, 64, 0], [10, 66, 1])),
+                Portal::new_test(([1000, 64, 0], [1000, 66, 1])),
+            ],
+        };
+        let results = outgoing_link_results(&portals, Entity::PLAYER, usize::MAX);
+        assert_eq!(results.len(), 3);
+        for (dimension, portal) in [
+            (Overworld, &portals.overworld[0]),
+            (Nether, &portals.nether[0]),
+            (Nether, &portals.nether[1]),
+        ] {
+            let expected = link_result(&portals, portal, dimension, Entity::PLAYER, usize::MAX);
+            assert!(results.contains(&(portal.id, expected)));
+        }
+        assert!(results.contains(&(
+            portals.overworld[0].id,
+            PortalLinkResult::Portals {
+                ids: vec![portals.nether[0].id],
+                new_portal: false,
+            },
+        )));
+    }
+
+    #[test]
+    fn test_find_loop() {
+        let ids: [PortalId; 4] = std::array::from_fn(|_| PortalId::new());
+        let link = |to: &[usize]| {
+            let ids = to.iter().map(|&i| ids[i]).collect();
+            let result = PortalLinkResult::Portals {
+                ids,
+                new_portal: false,
+            };
+            (result, vec![])
+        };
+
+        // 0 <-> 1 is a clean pair
+        let mut links = HashMap::from([(ids[0], link(&[1])), (ids[1], link(&[0]))]);
+        assert_eq!(find_loop(&links, ids[0]), None);
+
+        // 0 -> 1 -> 2 -> 3 -> 0
+        links.insert(ids[1], link(&[0, 2]));
+        links.insert(ids[2], link(&[3]));
+        links.insert(ids[3], link(&[0]));
+        assert_eq!(find_loop(&links, ids[0]), Some(ids.to_vec()));
+        assert_eq!(
+            find_loop(&links, ids[2]),
+            Some(vec![ids[2], ids[3], ids[0], ids[1]]),
+        );
+    }
+
+    #[test]
+    fn test_unmet_intended_links() {
+        let a = Portal::new_test(([0, 64, 0], [0, 66, 1]));
+        let b = Portal::new_test(([0, 64, 0], [0, 66, 1]));
+        let c = Portal::new_test(([8, 64, 0], [8, 66, 1]));
+        let mut world = World::default();
+        world.portals.overworld = vec![a.clone()];
+        world.portals.nether = vec![b.clone(), c.clone()];
+        world.intended_links = HashMap::from([(a.id, b.id), (PortalId::new(), b.id)]);
+
+        let link = |ids: Vec<PortalId>| {
+            let result = PortalLinkResult::Portals {
+                ids,
+                new_portal: false,
+            };
+            (result, vec![])
+        };
+        let mut links = HashMap::from([(a.id, link(vec![b.id]))]);
+        assert!(is_intended_link_met(&links, a.id, b.id));
+        assert_eq!(unmet_intended_links(&world, &links), []);
+
+        links.insert(a.id, link(vec![b.id, c.id]));
+        assert!(!is_intended_link_met(&links, a.id, b.id));
+        assert_eq!(unmet_intended_links(&world, &links), [(a.id, b.id)]);
+    }
+}
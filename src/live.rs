@@ -0,0 +1,160 @@
+//! Live position of the player, read from a file written by the game or a mod.
+
+use std::fs::File;
+use std::io::{Read, Seek, SeekFrom};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+
+use crate::parse::{mentions_unsupported_dimension, parse_coordinates, parse_dimension};
+use crate::{Dimension, Overworld, WorldPos};
+
+/// How often to read the file.
+pub const POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+/// Position of the player, read periodically from a text file.
+#[derive(Debug)]
+pub struct LivePosition {
+    path: PathBuf,
+    last_read: Option<web_time::Instant>,
+    /// Number of bytes at the start of the file that have already been read,
+    /// up to the end of the last complete line.
+    offset: u64,
+    /// Modification time of the file when it was last read.
+    modified: Option<SystemTime>,
+    /// Most recent valid position, which is kept if the file later becomes
+    /// unreadable or malformed.
+    pos: Option<(Dimension, WorldPos)>,
+}
+
+impl LivePosition {
+    /// Constructs a live position that reads from `path`.
+    pub fn new(path: PathBuf) -> Self {
+        Self {
+            path,
+            last_read: None,
+            offset: 0,
+            modified: None,
+            pos: None,
+        }
+    }
+
+    /// Returns the path of the file.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Returns the most recent valid position.
+    pub fn pos(&self) -> Option<(Dimension, WorldPos)> {
+        self.pos
+    }
+
+    /// Reads new lines from the file if [`POLL_INTERVAL`] has elapsed since it
+    /// was last read.
+    pub fn poll(&mut self) {
+        let now = web_time::Instant::now();
+        if self.last_read.is_none_or(|t| now - t >= POLL_INTERVAL) {
+            self.last_read = Some(now);
+            match self.read_new_text() {
+                Ok(text) => match parse_live_position(&text) {
+                    Some(pos) => self.pos = Some(pos),
+                    None => log::debug!("no new coordinates in live position file {:?}", self.path),
+                },
+                Err(e) => log::debug!("error reading live position file {:?}: {e}", self.path),
+            }
+        }
+    }
+
+    /// Returns the text that has been added to the file since it was last read.
+    /// If the file has been rewritten instead of appended to, then the whole
+    /// file is returned.
+    fn read_new_text(&mut self) -> std::io::Result<String> {
+        let mut file = File::open(&self.path)?;
+        let metadata = file.metadata()?;
+        let modified = metadata.modified().ok();
+        let len = metadata.len();
+        if len < self.offset || (len == self.offset && modified != self.modified) {
+            self.offset = 0;
+        }
+        self.modified = modified;
+
+        file.seek(SeekFrom::Start(self.offset))?;
+        let mut bytes = vec![];
+        file.read_to_end(&mut bytes)?;
+        // Read the last line again next time in case it is incomplete.
+        if let Some(i) = bytes.iter().rposition(|&b| b == b'\n') {
+            self.offset += i as u64 + 1;
+        }
+        Ok(String::from_utf8_lossy(&bytes).into_owned())
+    }
+}
+
+/// Parses the last line of `text` that contains coordinates, along with the
+/// dimension mentioned on that line. If no dimension is mentioned, the
+/// overworld is assumed. Lines in other dimensions, such as the End, are
+/// skipped.
+///
+/// This accepts the output of F3+C, such as `/execute in minecraft:the_nether
+/// run tp @s 12.5 64.0 -30.5 0.0 0.0`.
+fn parse_live_position(text: &str) -> Option<(Dimension, WorldPos)> {
+    text.lines().rev().find_map(|line| {
+        if mentions_unsupported_dimension(line) {
+            return None;
+        }
+        let pos = parse_coordinates(line)?;
+        Some((parse_dimension(line).unwrap_or(Overworld), pos))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Nether;
+
+    #[test]
+    fn test_parse_live_position() {
+        let pos = |x, y, z| WorldPos { x, y, z };
+        assert_eq!(parse_live_position(""), None);
+        assert_eq!(parse_live_position("garbage\n\u{fffd}"), None);
+        assert_eq!(
+            parse_live_position("1 2 3\n4 5 6\nnot coordinates\n"),
+            Some((Overworld, pos(4.0, 5.0, 6.0))),
+        );
+        assert_eq!(
+            parse_live_position(
+                "/execute in minecraft:the_nether run tp @s 12.5 64.0 -30.5 0.0 0.0"
+            ),
+            Some((Nether, pos(12.5, 64.0, -30.5))),
+        );
+        assert_eq!(
+            parse_live_position(
+                "1 2 3\n/execute in minecraft:the_end run tp @s 12.5 64.0 -30.5 0.0 0.0"
+            ),
+            Some((Overworld, pos(1.0, 2.0, 3.0))),
+        );
+    }
+
+    #[test]
+    fn test_poll_reads_new_lines() {
+        let path = std::env::temp_dir().join(format!(
+            "{}-test-live-position-{}.txt",
+            crate::APP_ID,
+            std::process::id(),
+        ));
+        let mut live = LivePosition::new(path.clone());
+        let mut poll = |contents: &str| {
+            std::fs::write(&path, contents).expect("error writing file");
+            live.last_read = None;
+            live.poll();
+            (live.offset, live.pos.map(|(_, pos)| pos.x))
+        };
+
+        assert_eq!(poll("1 2 3\n"), (6, Some(1.0)));
+        // Appended lines are read from where the last read ended.
+        assert_eq!(poll("1 2 3\n4 5 6\n7 8"), (12, Some(4.0)));
+        assert_eq!(poll("1 2 3\n4 5 6\n7 8 9\n"), (18, Some(7.0)));
+        // A shorter file has been rewritten, so it is read from the start.
+        assert_eq!(poll("2 3 4\n"), (6, Some(2.0)));
+
+        std::fs::remove_file(&path).expect("error removing file");
+    }
+}
@@ -5,6 +5,7 @@
 use core::f32;
 use std::collections::{HashMap, HashSet};
 use std::future::Future;
+use std::ops::RangeInclusive;
 use std::path::PathBuf;
 
 use egui::Widget;
@@ -12,28 +13,51 @@ use egui::emath::GuiRounding;
 use oneshot::TryRecvError;
 use serde::{Deserialize, Serialize};
 
+mod annotation;
+mod arrange;
+mod batch;
 mod camera;
+mod checklist;
+mod diff;
 mod entity;
+mod export;
 mod id;
+mod links;
+#[cfg(not(target_arch = "wasm32"))]
+mod live;
+mod parse;
 mod portal;
 mod pos;
+#[cfg(not(target_arch = "wasm32"))]
+mod recovery;
 mod region;
 mod threads;
+mod travel;
 mod util;
 mod world;
 
 pub use Dimension::{Nether, Overworld};
+pub use annotation::{Area, Route};
 pub use camera::{Camera, Plane};
 pub use entity::Entity;
 pub use id::PortalId;
 use itertools::Itertools;
-pub use portal::{Portal, PortalAxis};
+pub use portal::{EntityWontFit, NewPortalPlacement, Portal, PortalAxis, PortalTemplate};
 pub use pos::{Axis, BlockPos, WorldPos};
 pub use region::{BlockRegion, WorldRegion};
 use threads::AsyncSafe;
-pub use world::{ConvertDimension, Dimension, World, WorldPortals};
+use travel::TravelSpeed;
+use world::{ArrowColoring, Theme};
+pub use world::{
+    ConvertDimension, Dimension, DisplayOverrides, PerDimension, World, WorldFilter, WorldPortals,
+};
 
 const TITLE: &str = "Portal Planner";
+
+/// Hover text for settings that are overridden by the display settings saved
+/// with the world.
+const SAVED_DISPLAY_SETTING_HOVER_TEXT: &str =
+    "Saved with this file. Remove the display settings from the file to change it.";
 const APP_ID: &str = "PortalPlanner";
 const ICON_256_PNG_DATA: &[u8] = include_bytes!("../resources/icon/portalplanner_256x256.png");
 
@@ -44,6 +68,90 @@ pub const PLOT_MARGIN: f32 = 8.0;
 
 /// Animation speed when switching dimensions.
 pub const ANIMATION_SPEED: f64 = 8.0;
+/// Duration in seconds of the highlight on portals whose link changed.
+pub const LINK_CHANGED_FLASH_DURATION: f64 = 1.5;
+
+/// Range of arrow tip lengths in the plot, in points.
+pub const ARROW_TIP_LENGTH_RANGE: RangeInclusive<f32> = 4.0..=16.0;
+/// Range of test point marker radii in the plot, in points.
+pub const TEST_POINT_RADIUS_RANGE: RangeInclusive<f32> = 2.0..=8.0;
+/// Radius of the crosshair at the camera position, in points.
+pub const CROSSHAIR_RADIUS: f32 = 6.0;
+/// Range of multipliers for mouse wheel and trackpad zooming and scrolling.
+pub const SCROLL_SENSITIVITY_RANGE: RangeInclusive<f32> = 0.1..=4.0;
+
+/// Entities that can be selected from the entity size menu.
+const ENTITY_PRESETS: [(&str, Entity); 6] = [
+    ("Player", Entity::PLAYER),
+    ("Ghast", Entity::GHAST),
+    ("Item", Entity::ITEM),
+    ("Arrow", Entity::ARROW),
+    ("Ender pearl", Entity::ENDER_PEARL),
+    ("Geometric", Entity::POINT),
+];
+
+/// Returns the entity presets other than [`Entity::POINT`], which is not a
+/// real entity and is left out of annotations about which entities take a
+/// link.
+fn real_entity_presets() -> impl Iterator<Item = (&'static str, Entity)> {
+    ENTITY_PRESETS
+        .into_iter()
+        .filter(|&(_, entity)| entity != Entity::POINT)
+}
+
+/// Distance from the origin to the world border along the X or Z axis.
+const WORLD_BORDER_DISTANCE: i64 = 29_999_984;
+
+/// Outline colors for the collision regions of each of [`ENTITY_PRESETS`] when
+/// comparing several entities on a hovered portal.
+const ENTITY_OUTLINE_COLORS: [egui::Color32; 6] = [
+    egui::Color32::from_rgb(0x40, 0xa0, 0xff),
+    egui::Color32::from_rgb(0xff, 0x60, 0xc0),
+    egui::Color32::from_rgb(0xff, 0xb0, 0x20),
+    egui::Color32::from_rgb(0x40, 0xe0, 0x40),
+    egui::Color32::from_rgb(0xb0, 0x60, 0xff),
+    egui::Color32::from_rgb(0x40, 0xe0, 0xe0),
+];
+
+/// Maximum number of decimal places when editing entity sizes.
+const ENTITY_SIZE_MAX_DECIMALS: usize = 4;
+
+/// Maximum distance in blocks from the "Go to Coordinates" target at which
+/// portals are highlighted.
+const GO_TO_HIGHLIGHT_DISTANCE: i64 = 8;
+
+/// Outline color for portals only in the world being compared against.
+const COMPARISON_ADDED_COLOR: egui::Color32 = egui::Color32::from_rgb(0x40, 0xe0, 0x40);
+/// Outline color for portals missing from the world being compared against.
+const COMPARISON_REMOVED_COLOR: egui::Color32 = egui::Color32::from_rgb(0xff, 0x40, 0x40);
+/// Outline color for portals that are elsewhere in the world being compared
+/// against.
+const COMPARISON_MOVED_COLOR: egui::Color32 = egui::Color32::from_rgb(0xff, 0xb0, 0x20);
+
+/// Arrow color for intended links that are not met.
+const UNMET_INTENDED_LINK_COLOR: egui::Color32 = egui::Color32::from_rgb(0xff, 0x40, 0x40);
+
+/// Color of the icon for portals that link to exactly one portal.
+const LINK_OK_COLOR: egui::Color32 = egui::Color32::from_rgb(0x40, 0xc0, 0x40);
+/// Arrow color for ambiguous links when coloring arrows by link quality.
+const LINK_AMBIGUOUS_COLOR: egui::Color32 = egui::Color32::from_rgb(0xff, 0xd0, 0x20);
+/// Arrow color for links from portals that may also generate a new portal when
+/// coloring arrows by link quality.
+const LINK_NEW_PORTAL_COLOR: egui::Color32 = egui::Color32::from_rgb(0xff, 0x40, 0x40);
+
+/// Arrow colors for links into each dimension when coloring arrows by
+/// dimension.
+const DIMENSION_ARROW_COLORS: PerDimension<egui::Color32> = PerDimension {
+    overworld: egui::Color32::from_rgb(0x40, 0xa0, 0xff),
+    nether: egui::Color32::from_rgb(0xff, 0x70, 0x30),
+};
+
+/// Color of the marker at the player's live position.
+#[cfg(not(target_arch = "wasm32"))]
+const LIVE_POSITION_COLOR: egui::Color32 = egui::Color32::from_rgb(0x40, 0xa0, 0xff);
+
+/// Outline color for portals whose link recently changed.
+const LINK_CHANGED_COLOR: egui::Color32 = egui::Color32::from_rgb(0x40, 0xc0, 0xff);
 
 #[allow(missing_docs)]
 mod kbd_shortcuts {
@@ -58,6 +166,7 @@ mod kbd_shortcuts {
 
     pub const SWITCH_DIMENSIONS: Shortcut = Shortcut::new(Mods::NONE, Key::Space);
     pub const RESET_CAMERA: Shortcut = Shortcut::new(Mods::NONE, Key::Escape);
+    pub const ADD_PORTAL: Shortcut = Shortcut::new(Mods::NONE, Key::A);
 
     pub const NEW: Shortcut = Shortcut::new(Mods::COMMAND, Key::N);
     pub const IMPORT_EXPORT: Shortcut = Shortcut::new(Mods::COMMAND, Key::E);
@@ -65,6 +174,16 @@ mod kbd_shortcuts {
     pub const SAVE: Shortcut = Shortcut::new(Mods::COMMAND, Key::S);
     pub const SAVE_AS: Shortcut = Shortcut::new(Mods::COMMAND.plus(Mods::SHIFT), Key::S);
     pub const QUIT: Shortcut = Shortcut::new(Mods::COMMAND, Key::Q);
+
+    /// Number keys for selecting each of [`crate::ENTITY_PRESETS`].
+    pub const ENTITY_PRESETS: [Shortcut; 6] = [
+        Shortcut::new(Mods::NONE, Key::Num1),
+        Shortcut::new(Mods::NONE, Key::Num2),
+        Shortcut::new(Mods::NONE, Key::Num3),
+        Shortcut::new(Mods::NONE, Key::Num4),
+        Shortcut::new(Mods::NONE, Key::Num5),
+        Shortcut::new(Mods::NONE, Key::Num6),
+    ];
 }
 
 // Native
@@ -144,36 +263,108 @@ fn main() {
 pub struct Preferences {
     show_all_labels: bool,
     show_all_arrows: bool,
+    /// Which links of the hovered or solo portal to show arrows for.
+    focused_link_direction: LinkDirection,
     arrow_coloring: ArrowColoring,
+    /// Names of entity presets whose collision regions are outlined on hovered
+    /// portals, in addition to the current entity.
+    outline_entities: Vec<String>,
 
     show_zy_plot: bool,
+    show_minimap: bool,
+    show_crosshair: bool,
+    positive_z_up: bool,
+    keep_blocks_square: bool,
+    /// Whether switching dimensions restores the camera last used in that
+    /// dimension instead of converting the current camera position.
+    independent_cameras: bool,
     show_both_portal_lists: bool,
+    show_converted_coordinates: bool,
+    preview_links_at_cursor: bool,
+    /// Whether to show where an entity at the camera position would link to.
+    preview_links_at_camera: bool,
 
     hover_either_dimension: bool,
     lock_portal_size: bool,
+    /// Increment to snap coordinates to while dragging with Alt held.
+    snap_increment: i64,
+    /// Number of steps the fast link search may take before falling back to
+    /// checking every block.
+    link_search_step_limit: usize,
+    /// Mode of travel used to estimate travel times.
+    travel_speed: TravelSpeed,
+    /// Whether to warn about portals far from the origin.
+    warn_far_portals: bool,
+    /// Distance from the origin beyond which portals are considered far.
+    far_portal_distance: i64,
+    /// Multiplier for zooming with the mouse wheel or trackpad.
+    zoom_sensitivity: f32,
+    /// Multiplier for scrolling and panning with the mouse wheel or trackpad.
+    scroll_sensitivity: f32,
     entity: Entity,
 
-    #[cfg(not(target_arch = "wasm32"))]
+    new_portal_axis: PerDimension<PortalAxis>,
+    /// Width and height of new portals.
+    new_portal_size: [i64; 2],
+    remember_portal_axis: bool,
+    new_portal_placement: NewPortalPlacement,
+    /// Portal configurations that can be added with one click.
+    portal_templates: Vec<PortalTemplate>,
+
     autosave: bool,
+    save_portal_ids: bool,
+    /// Whether opening a file can be undone, restoring the previous world.
+    undo_across_files: bool,
     file_path: Option<PathBuf>,
+    /// User's own theme, remembered while the theme saved with a world is
+    /// applied.
+    user_theme: Option<egui::ThemePreference>,
 }
 impl Default for Preferences {
     fn default() -> Self {
         Self {
             show_all_labels: true,
             show_all_arrows: false,
+            focused_link_direction: LinkDirection::default(),
             arrow_coloring: ArrowColoring::default(),
+            outline_entities: vec![],
 
             show_zy_plot: true,
+            show_minimap: true,
+            show_crosshair: true,
+            positive_z_up: false,
+            keep_blocks_square: false,
+            independent_cameras: false,
             show_both_portal_lists: false,
+            show_converted_coordinates: false,
+            preview_links_at_cursor: true,
+            preview_links_at_camera: false,
 
             hover_either_dimension: true,
             lock_portal_size: true,
+            snap_increment: 16,
+            link_search_step_limit: world::DEFAULT_LINK_SEARCH_STEP_LIMIT,
+            travel_speed: TravelSpeed::default(),
+            warn_far_portals: false,
+            far_portal_distance: 1_000_000,
+            zoom_sensitivity: 1.0,
+            scroll_sensitivity: 1.0,
             entity: Entity::PLAYER,
 
-            #[cfg(not(target_arch = "wasm32"))]
+            new_portal_axis: PerDimension {
+                overworld: PortalAxis::X,
+                nether: PortalAxis::X,
+            },
+            new_portal_size: [Portal::MIN_WIDTH, Portal::MIN_HEIGHT],
+            remember_portal_axis: false,
+            new_portal_placement: NewPortalPlacement::default(),
+            portal_templates: PortalTemplate::defaults(),
+
             autosave: true,
+            save_portal_ids: false,
+            undo_across_files: false,
             file_path: None,
+            user_theme: None,
         }
     }
 }
@@ -186,30 +377,122 @@ impl Preferences {
 pub struct App {
     world: World,
     camera: Camera,
+    /// Camera last used in each dimension.
+    saved_cameras: PerDimension<Option<Camera>>,
     animation_state: AnimationState,
 
     portals_hovered: PortalHoverState,
+    /// Empty position under the cursor in the plot, from which to preview
+    /// links.
+    link_preview: Option<(Dimension, WorldPos)>,
+    link_preview_for_next_frame: Option<(Dimension, WorldPos)>,
+    /// Portal that was right-clicked in the plot.
+    plot_context_menu_portal: Option<PortalId>,
+    /// Selected portals, in the order they were selected.
+    selected_portals: Vec<PortalId>,
+    /// Portals whose coordinates are edited in the other dimension's units.
+    portals_edited_in_other_units: HashSet<PortalId>,
+    /// Portals hidden in the plots. Hidden portals still affect linking.
+    hidden_portals: HashSet<PortalId>,
+    /// Portal to show in the plots along with only the portals it links to
+    /// and from.
+    solo_portal: Option<PortalId>,
+    /// Selected portals being dragged in the plot.
+    portal_drag: Option<PortalDrag>,
 
     unsaved_changes: bool,
     last_frame_state: World,
-    undo_history: Vec<World>,
-    redo_history: Vec<World>,
+    undo_history: Vec<HistoryEntry>,
+    redo_history: Vec<HistoryEntry>,
 
     cached_state: (World, Entity),
+    /// Whether `cached_links` is out of date, which is checked once at the
+    /// start of each frame because comparing worlds is slow.
+    links_stale: bool,
     cached_links: HashMap<PortalId, (PortalLinkResult, Vec<PortalId>)>,
+    /// Names of the entity presets for which each source portal links to each
+    /// destination portal, keyed by source portal. This is only computed for
+    /// hovered portals and the portals that link to them.
+    cached_link_entities: HashMap<PortalId, HashMap<PortalId, Vec<&'static str>>>,
+    /// Best shortcut through the nether from each overworld portal, computed
+    /// along with `cached_links`.
+    cached_shortcuts: HashMap<PortalId, travel::Shortcut>,
+    /// Shortest loop of links through each portal that is part of one,
+    /// computed along with `cached_links`.
+    cached_loops: HashMap<PortalId, Vec<PortalId>>,
+    /// Lookup from portal ID to dimension and index, updated every frame.
+    portal_lookup: world::PortalIndex,
 
     prefs: Preferences,
 
     import_export_modal_text: Option<String>,
-    cached_import_export_modal_text_deserialized: Option<serde_json::Result<World>>,
+    /// Deserialized world and duplicate portal IDs from the import/export
+    /// modal text.
+    cached_import_export_modal_text_deserialized: Option<serde_json::Result<(World, Vec<u64>)>>,
+    /// Parts of the world to import from the import/export modal.
+    import_filter: WorldFilter,
+    /// Whether the import/export modal merges into the current world instead
+    /// of replacing it.
+    import_merge: bool,
+
+    build_checklist_modal_text: Option<String>,
+    /// Dimension and input text for the "Batch Evaluate" modal.
+    batch_evaluate_modal: Option<(Dimension, String)>,
+    /// Output of the "Batch Evaluate" modal, along with the portals, dimension,
+    /// and input text it was computed from.
+    cached_batch_evaluate_output: Option<((WorldPortals, Dimension, String), String)>,
+    annotations_window_open: bool,
+    explain_link_window_open: bool,
+    stats_window_open: bool,
+    go_to_window_open: bool,
+    templates_window_open: bool,
+    /// Position entered in the "Go to Coordinates" window.
+    go_to_pos: (Dimension, WorldPos),
+    /// Position to mark in the plot, near which portals are highlighted.
+    go_to_target: Option<(Dimension, WorldPos)>,
+    /// Portal to move to the coordinates in the clipboard when they arrive.
+    paste_coordinates_target: Option<PortalId>,
+    /// Theme from the display settings saved with the world that was most
+    /// recently applied.
+    applied_theme_override: Option<Theme>,
+    /// Text entered in the "Paste Coordinates" modal.
+    paste_coordinates_modal: Option<String>,
+    /// Portal and corners entered in the "Set Corners" modal.
+    set_corners_modal: Option<(PortalId, BlockPos, BlockPos)>,
+    /// Route to which clicking in the plot adds points.
+    drawing_route: Option<(Dimension, usize)>,
+    /// Dimension and offset for the "Offset All" modal.
+    offset_all_modal: Option<(Dimension, BlockPos)>,
+    /// World to merge, along with the dimension and offset to apply to it.
+    merge_modal: Option<(World, Dimension, BlockPos)>,
+    /// Filter for each portal list.
+    portal_filter: PerDimension<PortalFilter>,
+    /// Portals to delete after confirmation.
+    delete_filtered_modal: Option<(Dimension, Vec<PortalId>)>,
+    /// World to compare against, along with its differences from the current
+    /// world.
+    comparison: Option<(World, diff::WorldDiff)>,
 
     /// Task to complete before re-enabling the UI.
     ///
     /// If this is `Some`, then the UI is disabled.
     async_task: Option<oneshot::Receiver<Result<AppAsyncTaskOk, AppAsyncTaskErr>>>,
+
+    /// Whether unsaved work from a previous session was restored on startup.
+    restored_unsaved_work: bool,
+    /// Backup of unsaved changes, in case the app crashes.
+    #[cfg(not(target_arch = "wasm32"))]
+    recovery_file: recovery::RecoveryFile,
+    /// Player position read from a file, shown as a marker in the plot.
+    #[cfg(not(target_arch = "wasm32"))]
+    live_position: Option<live::LivePosition>,
 }
 
 impl App {
+    /// Storage key for the world, which is autosaved on web.
+    #[cfg(target_arch = "wasm32")]
+    const WORLD_STORAGE_KEY: &str = "world";
+
     /// Constructs the application state.
     pub fn new(cc: &eframe::CreationContext<'_>) -> Self {
         cc.egui_ctx.style_mut(|style| {
@@ -219,14 +502,46 @@ impl App {
 
         let storage = cc.storage.as_ref();
 
-        App {
+        let mut app = App {
             prefs: storage
                 .and_then(|storage| storage.get_string(Preferences::STORAGE_KEY))
                 .and_then(|s| serde_json::from_str(&s).ok())
                 .unwrap_or_default(),
 
             ..Default::default()
+        };
+
+        #[cfg(target_arch = "wasm32")]
+        if app.prefs.autosave
+            && let Some(world) = storage
+                .and_then(|storage| storage.get_string(Self::WORLD_STORAGE_KEY))
+                .and_then(|s| World::from_json(&s).ok())
+            && world != World::default()
+        {
+            app.load(world);
+            app.unsaved_changes = true;
+            app.restored_unsaved_work = true;
+        }
+
+        #[cfg(not(target_arch = "wasm32"))]
+        if let Some((world, file_path)) = recovery::take_previous()
+            && rfd::MessageDialog::new()
+                .set_level(rfd::MessageLevel::Warning)
+                .set_title("Restore unsaved work?")
+                .set_description(format!(
+                    "{TITLE} did not exit cleanly last time. \
+                     Restore unsaved changes from the previous session?",
+                ))
+                .set_buttons(rfd::MessageButtons::YesNo)
+                .show()
+                == rfd::MessageDialogResult::Yes
+        {
+            app.load(world);
+            app.prefs.file_path = file_path;
+            app.unsaved_changes = true;
         }
+
+        app
     }
 
     /// Returns `true` if the current file is saved or if the user confirms
@@ -246,17 +561,98 @@ impl App {
             self.load(World::default());
         }
     }
+    fn load_example(&mut self) {
+        if self.is_ok_to_discard_state() {
+            self.load(World::example());
+        }
+    }
     fn load(&mut self, world: World) {
-        self.world = world.clone();
+        let old_world = std::mem::replace(&mut self.world, world.clone());
         self.last_frame_state = world;
-        self.undo_history = vec![];
+        if self.prefs.undo_across_files {
+            self.undo_history.push(HistoryEntry::Load {
+                world: old_world,
+                file_path: self.prefs.file_path.clone(),
+            });
+        } else {
+            self.undo_history = vec![];
+        }
         self.redo_history = vec![];
         self.unsaved_changes = false;
+        self.restored_unsaved_work = false;
+        self.selected_portals = vec![];
+        self.drawing_route = None;
+        self.comparison = None;
         self.prefs.file_path = None;
     }
 
+    /// Returns whether to show the ZY plot, which is remembered for each file.
+    fn show_zy_plot(&self) -> bool {
+        self.world.show_zy_plot.unwrap_or(self.prefs.show_zy_plot)
+    }
+    /// Shows or hides the ZY plot for the current file and for new files.
+    fn set_show_zy_plot(&mut self, show: bool) {
+        self.world.show_zy_plot = Some(show);
+        self.prefs.show_zy_plot = show;
+    }
+
+    /// Returns whether to show labels for all portals, which may be
+    /// overridden by the display settings saved with the world.
+    fn show_all_labels(&self) -> bool {
+        self.world
+            .display
+            .show_all_labels
+            .unwrap_or(self.prefs.show_all_labels)
+    }
+    /// Returns whether to show arrows for all links, which may be overridden
+    /// by the display settings saved with the world.
+    fn show_all_arrows(&self) -> bool {
+        self.world
+            .display
+            .show_all_arrows
+            .unwrap_or(self.prefs.show_all_arrows)
+    }
+    /// Returns how to color the arrows for links, which may be overridden by
+    /// the display settings saved with the world.
+    fn arrow_coloring(&self) -> ArrowColoring {
+        self.world
+            .display
+            .arrow_coloring
+            .unwrap_or(self.prefs.arrow_coloring)
+    }
+
+    /// Applies the theme saved with the world when it changes, remembering the
+    /// user's own theme and restoring it once the world no longer has one.
+    fn update_theme(&mut self, ctx: &egui::Context) {
+        let theme_override = self.world.display.theme;
+        if theme_override != self.applied_theme_override {
+            if let Some(theme) = theme_override {
+                (self.prefs.user_theme)
+                    .get_or_insert_with(|| ctx.options(|options| options.theme_preference));
+                ctx.set_theme(egui::ThemePreference::from(theme));
+            }
+            self.applied_theme_override = theme_override;
+        }
+        if theme_override.is_none()
+            && let Some(theme) = self.prefs.user_theme.take()
+        {
+            ctx.set_theme(theme);
+        }
+    }
+
+    /// Gives each portal that does not have a file ID a new one, so that it
+    /// keeps the same ID in every file it is saved to.
+    fn assign_file_ids(&mut self) {
+        let unchanged = self.last_frame_state == self.world;
+        self.world.assign_file_ids();
+        if unchanged {
+            self.last_frame_state = self.world.clone();
+        }
+    }
+
     fn toggle_import_export(&mut self) {
-        match serde_json::to_string_pretty(&self.world) {
+        self.assign_file_ids();
+        match self.world.to_json(self.prefs.save_portal_ids) {
             Ok(s) => self.import_export_modal_text = Some(s),
             Err(e) => show_error_dialog(("Export error", e)),
         }
@@ -267,25 +663,32 @@ impl App {
             return;
         }
         self.spawn_async_task(async move || {
-            match rfd::AsyncFileDialog::new()
-                .add_filter("JSON", &["json"])
-                .pick_file()
-                .await
-            {
-                Some(file_handle) => {
-                    let contents = file_handle.read().await;
-                    let world = serde_json::from_slice(&contents)
-                        .map_err(|e| ("Error deserializing file", e))?;
-                    Ok(AppAsyncTaskOk::Load {
-                        #[cfg(not(target_arch = "wasm32"))]
-                        path: Some(file_handle.path().to_path_buf()),
-                        #[cfg(target_arch = "wasm32")]
-                        path: None,
-                        world,
-                    })
-                }
-                None => Ok(AppAsyncTaskOk::None),
-            }
+            Ok(match pick_world_file().await? {
+                Some((_file_handle, world)) => AppAsyncTaskOk::Load {
+                    #[cfg(not(target_arch = "wasm32"))]
+                    path: Some(_file_handle.path().to_path_buf()),
+                    #[cfg(target_arch = "wasm32")]
+                    path: None,
+                    world,
+                },
+                None => AppAsyncTaskOk::None,
+            })
+        });
+    }
+    fn merge(&mut self) {
+        self.spawn_async_task(async move || {
+            Ok(match pick_world_file().await? {
+                Some((_, world)) => AppAsyncTaskOk::Merge { world },
+                None => AppAsyncTaskOk::None,
+            })
+        });
+    }
+    fn compare(&mut self) {
+        self.spawn_async_task(async move || {
+            Ok(match pick_world_file().await? {
+                Some((_, world)) => AppAsyncTaskOk::Compare { world },
+                None => AppAsyncTaskOk::None,
+            })
         });
     }
     fn save(&mut self) {
@@ -295,7 +698,8 @@ impl App {
         self.save_internal(None);
     }
     fn save_internal(&mut self, path: Option<PathBuf>) {
-        let serialization_result = serde_json::to_string_pretty(&self.world);
+        self.assign_file_ids();
+        let serialization_result = self.world.to_json(self.prefs.save_portal_ids);
         self.spawn_async_task(async move || {
             let contents_to_write =
                 serialization_result.map_err(|e| ("Error serializing file", e))?;
@@ -330,6 +734,22 @@ impl App {
         });
     }
 
+    fn export(&mut self, contents: String, filter_name: &'static str, extension: &'static str) {
+        self.spawn_async_task(async move || {
+            let out = rfd::AsyncFileDialog::new()
+                .add_filter(filter_name, &[extension])
+                .save_file()
+                .await;
+            if let Some(file_handle) = out {
+                file_handle
+                    .write(contents.as_bytes())
+                    .await
+                    .map_err(|e| ("Error exporting file", e))?;
+            }
+            Ok(AppAsyncTaskOk::None)
+        });
+    }
+
     fn spawn_async_task<
         F: 'static + AsyncSafe + Future<Output = Result<AppAsyncTaskOk, AppAsyncTaskErr>>,
     >(
@@ -337,7 +757,8 @@ impl App {
         f: impl FnOnce() -> F,
     ) {
         if self.async_task.is_some() {
-            log::error!("cannot spawn async task; one is already running");
+            log::warn!("cannot spawn async task; one is already running");
+            return;
         }
         let (tx, rx) = oneshot::channel();
         let task = f();
@@ -346,29 +767,55 @@ impl App {
     }
 
     fn undo(&mut self) {
-        if let Some(new_state) = self.undo_history.pop() {
-            let old_state = std::mem::replace(&mut self.world, new_state);
-            self.last_frame_state = self.world.clone();
-            self.redo_history.push(old_state);
-            self.unsaved_changes = true;
+        if let Some(entry) = self.undo_history.pop() {
+            let entry = self.restore_history_entry(entry);
+            self.redo_history.push(entry);
         }
     }
     fn redo(&mut self) {
-        if let Some(new_state) = self.redo_history.pop() {
-            let old_state = std::mem::replace(&mut self.world, new_state);
-            self.last_frame_state = self.world.clone();
-            self.undo_history.push(old_state);
-            self.unsaved_changes = true;
+        if let Some(entry) = self.redo_history.pop() {
+            let entry = self.restore_history_entry(entry);
+            self.undo_history.push(entry);
         }
     }
+    /// Restores the state in `entry` and returns an entry that restores the
+    /// current state.
+    fn restore_history_entry(&mut self, entry: HistoryEntry) -> HistoryEntry {
+        let entry = match entry {
+            HistoryEntry::Edit(mut world) => {
+                world.expanded_portals = self.world.expanded_portals.clone();
+                world.display = self.world.display;
+                world.show_zy_plot = self.world.show_zy_plot;
+                HistoryEntry::Edit(std::mem::replace(&mut self.world, world))
+            }
+            HistoryEntry::Load { world, file_path } => HistoryEntry::Load {
+                world: std::mem::replace(&mut self.world, world),
+                file_path: std::mem::replace(&mut self.prefs.file_path, file_path),
+            },
+        };
+        self.last_frame_state = self.world.clone();
+        self.unsaved_changes = true;
+        entry
+    }
 
     fn toggle_camera_dimension(&mut self) {
         self.set_camera_dimension(self.camera.dimension.other());
     }
     fn set_camera_dimension(&mut self, new_camera_dimension: Dimension) {
         if new_camera_dimension != self.camera.dimension {
+            self.saved_cameras[self.camera.dimension] = Some(self.camera);
+            // The saved camera already has the right scale, so don't animate
+            // rescaling it.
+            if self.prefs.independent_cameras
+                && let Some(saved_camera) = self.saved_cameras[new_camera_dimension]
+            {
+                self.camera = saved_camera;
+                return;
+            }
             let scale_factor = self.camera.dimension.scale() / new_camera_dimension.scale();
-            self.animation_state.aspect_ratio_scale /= scale_factor;
+            if !self.prefs.keep_blocks_square {
+                self.animation_state.aspect_ratio_scale /= scale_factor;
+            }
             self.camera.width *= scale_factor;
             self.camera.height *= scale_factor;
         }
@@ -405,32 +852,55 @@ impl App {
             ui.add(
                 egui::DragValue::new(&mut self.prefs.entity.width)
                     .range(0.0..=16.0)
-                    .speed(0.01),
-            );
+                    .speed(0.01)
+                    .max_decimals(ENTITY_SIZE_MAX_DECIMALS),
+            )
+            .on_hover_text("Hold Shift while dragging for finer control");
             coordinate_label(ui, "Height");
             ui.add(
                 egui::DragValue::new(&mut self.prefs.entity.height)
                     .range(0.0..=16.0)
-                    .speed(0.01),
-            );
+                    .speed(0.01)
+                    .max_decimals(ENTITY_SIZE_MAX_DECIMALS),
+            )
+            .on_hover_text("Hold Shift while dragging for finer control");
             ui.checkbox(&mut self.prefs.entity.is_projectile, "Projectile")
                 .on_hover_text(include_str!("text/projectile.txt").trim());
         });
 
+        let Entity {
+            width,
+            height,
+            is_projectile,
+        } = self.prefs.entity;
+        let half_width = self.prefs.entity.half_width();
+        ui.weak(if self.prefs.entity == Entity::POINT {
+            "Collides with exactly the portal blocks, ignoring hitboxes".to_owned()
+        } else if is_projectile {
+            format!(
+                "Collides with portal blocks extended by {half_width} \
+                 horizontally and {height} downward",
+            )
+        } else {
+            format!(
+                "Collides with portal blocks extended by {half_width} \
+                 horizontally, then shrunk by {width} along the portal's \
+                 width and {height} from the top",
+            )
+        });
+
         ui.separator();
 
         ui.horizontal_wrapped(|ui| {
-            for (name, entity) in [
-                ("Player", Entity::PLAYER),
-                ("Ghast", Entity::GHAST),
-                ("Item", Entity::ITEM),
-                ("Arrow", Entity::ARROW),
-                ("Ender pearl", Entity::ENDER_PEARL),
-            ] {
-                let mut atoms = egui::Atoms::new(name);
-                atoms.push_right(egui::Atom::grow());
-                atoms.push_right(egui::RichText::new(format!("{entity:.02}")).small());
-                ui.selectable_value(&mut self.prefs.entity, entity, name);
+            for ((name, entity), shortcut) in
+                std::iter::zip(ENTITY_PRESETS, &kbd_shortcuts::ENTITY_PRESETS)
+            {
+                let button = egui::Button::new(name)
+                    .selected(self.prefs.entity == entity)
+                    .shortcut_text(ui.ctx().format_shortcut(shortcut));
+                if ui.add(button).on_hover_text(entity.to_string()).clicked() {
+                    self.prefs.entity = entity;
+                }
             }
         });
     }
@@ -444,10 +914,7 @@ impl App {
                     .on_hover_text("Add portal")
                     .clicked()
                 {
-                    match dimension {
-                        Overworld => self.add_portal_in_overworld(),
-                        Nether => self.add_portal_in_nether(),
-                    }
+                    self.add_portal(dimension);
                 }
 
                 if big_img_button(ui, egui::include_image!("img/map-marker-plus.svg"))
@@ -456,6 +923,21 @@ impl App {
                 {
                     self.world.test_points[dimension].push(self.camera.pos);
                 }
+
+                ui.menu_button("Sort", |ui| {
+                    for key in PortalSortKey::ALL {
+                        if ui.button(key.label()).clicked() {
+                            self.sort_portals(dimension, key);
+                            ui.close();
+                        }
+                    }
+                });
+                if ui.button("Expand All").clicked() {
+                    self.set_all_portals_expanded(ui.ctx(), dimension, true);
+                }
+                if ui.button("Collapse All").clicked() {
+                    self.set_all_portals_expanded(ui.ctx(), dimension, false);
+                }
             });
 
             let mut new_camera_dimension = self.camera.dimension;
@@ -471,6 +953,37 @@ impl App {
             self.set_camera_dimension(new_camera_dimension);
         });
 
+        ui.horizontal(|ui| {
+            let filter = &mut self.portal_filter[dimension];
+            egui::TextEdit::singleline(&mut filter.name)
+                .hint_text("Filter by name")
+                .desired_width(120.0)
+                .show(ui);
+            ui.checkbox(&mut filter.orphans_only, "No incoming links");
+            if filter.is_active() {
+                if ui.small_button("Clear").clicked() {
+                    *filter = PortalFilter::default();
+                }
+                let filter = &self.portal_filter[dimension];
+                let matching = self.world.portals[dimension]
+                    .iter()
+                    .filter(|p| filter.matches(p, &self.cached_links))
+                    .map(|p| p.id)
+                    .collect_vec();
+                if ui
+                    .add_enabled(
+                        !matching.is_empty(),
+                        egui::Button::new(format!("Delete {} Filtered…", matching.len())).small(),
+                    )
+                    .clicked()
+                {
+                    self.delete_filtered_modal = Some((dimension, matching));
+                }
+            }
+        });
+
+        let portal_filter = self.portal_filter[dimension].clone();
+
         let portals_by_id = self.world.portals[dimension.other()]
             .iter()
             .map(|p| (p.id, p.clone()))
@@ -494,7 +1007,7 @@ impl App {
                             test_point.convert_dimension(dimension, self.camera.dimension);
                     }
 
-                    show_world_pos_edit(ui, test_point, Some(3));
+                    show_world_pos_edit(ui, test_point, dimension, Some(3));
 
                     let destination_portals = self
                         .world
@@ -529,10 +1042,34 @@ impl App {
 
         let mut reorder_drag_start = None;
         let mut reorder_drag_end = None;
+        let links_stale = self.links_stale;
+        let snap = self.prefs.snap_increment;
+        let shortcuts = &self.cached_shortcuts;
+        let portal_names: HashMap<PortalId, String> = self
+            .world
+            .portals
+            .iter()
+            .map(|p| (p.id, p.display_name().to_owned()))
+            .collect();
+        let portal_name = |id: PortalId| portal_names.get(&id).map_or("<unknown>", String::as_str);
+        let intended_link_options = self.world.portals[dimension.other()]
+            .iter()
+            .map(|p| (p.id, p.display_name().to_owned()))
+            .collect_vec();
+        let loops = &self.cached_loops;
         let mut remove = None;
         let mut show_in_plot = None;
+        let mut create_linked_pair = None;
+        let mut save_as_template = None;
+        let mut paste_coordinates = None;
+        let mut set_corners = None;
         let mut show_portal_list_contents = |ui: &mut egui::Ui| {
             for (i, portal) in self.world.portals[dimension].iter_mut().enumerate() {
+                if !portal_filter.matches(portal, &self.cached_links) {
+                    continue;
+                }
+                let distance_from_origin = portal.distance_from_origin();
+
                 ui.separator();
 
                 const OUTLINE_WIDTH: f32 = 2.0;
@@ -548,18 +1085,54 @@ impl App {
                             ui.vertical(|ui| {
                                 egui::collapsing_header::CollapsingState::load_with_default_open(
                                     ui.ctx(),
-                                    egui::Id::new(portal.id).with("header"),
-                                    false,
+                                    portal_header_id(portal.id),
+                                    self.world.expanded_portals.contains(&portal.id),
                                 )
                                 .show_header(ui, |ui| {
                                     egui::Sides::new().shrink_left().show(
                                         ui,
                                         |ui| {
+                                            let mut is_selected =
+                                                self.selected_portals.contains(&portal.id);
+                                            if ui
+                                                .checkbox(&mut is_selected, "")
+                                                .on_hover_text("Select")
+                                                .changed()
+                                            {
+                                                if is_selected {
+                                                    self.selected_portals.push(portal.id);
+                                                } else {
+                                                    self.selected_portals
+                                                        .retain(|&id| id != portal.id);
+                                                }
+                                            }
+
+                                            let (eye, hover_text) =
+                                                if self.hidden_portals.contains(&portal.id) {
+                                                    (
+                                                        egui::include_image!("img/eye-off.svg"),
+                                                        "Show in plot",
+                                                    )
+                                                } else {
+                                                    (
+                                                        egui::include_image!("img/eye.svg"),
+                                                        "Hide in plot",
+                                                    )
+                                                };
+                                            if img_button(ui, eye)
+                                                .on_hover_text(hover_text)
+                                                .clicked()
+                                            {
+                                                if !self.hidden_portals.remove(&portal.id) {
+                                                    self.hidden_portals.insert(portal.id);
+                                                }
+                                            }
+
                                             if img_button(
                                                 ui,
                                                 egui::include_image!("img/crosshairs.svg"),
                                             )
-                                            .on_hover_text("Show in plot")
+                                            .on_hover_text("Go to portal")
                                             .clicked()
                                             {
                                                 show_in_plot = Some(i);
@@ -583,46 +1156,373 @@ impl App {
                                             {
                                                 remove = Some(i);
                                             }
+
+                                            let is_solo = self.solo_portal == Some(portal.id);
+                                            if ui
+                                                .selectable_label(is_solo, "Solo")
+                                                .on_hover_text(
+                                                    "Show only this portal and the portals \
+                                                     it links to and from",
+                                                )
+                                                .clicked()
+                                            {
+                                                self.solo_portal = (!is_solo).then_some(portal.id);
+                                            }
+
+                                            if !links_stale
+                                                && let Some((outgoing, _)) =
+                                                    self.cached_links.get(&portal.id)
+                                            {
+                                                show_link_status_icon(ui, outgoing);
+                                            }
+
+                                            if !links_stale
+                                                && let Some((_, incoming)) =
+                                                    self.cached_links.get(&portal.id)
+                                            {
+                                                let text = format!("← {}", incoming.len());
+                                                if incoming.is_empty() {
+                                                    ui.colored_label(
+                                                        ui.visuals().warn_fg_color,
+                                                        text,
+                                                    )
+                                                    .on_hover_text("No portals link here");
+                                                } else {
+                                                    ui.weak(text).on_hover_text(format!(
+                                                        "{} portals link here",
+                                                        incoming.len(),
+                                                    ));
+                                                }
+                                            }
+
+                                            if let Some(&destination) =
+                                                self.world.intended_links.get(&portal.id)
+                                                && portal_names.contains_key(&destination)
+                                                && !links_stale
+                                                && !links::is_intended_link_met(
+                                                    &self.cached_links,
+                                                    portal.id,
+                                                    destination,
+                                                )
+                                            {
+                                                ui.colored_label(ui.visuals().error_fg_color, "⚠")
+                                                    .on_hover_text(format!(
+                                                        "Doesn't link only to {}",
+                                                        portal_name(destination),
+                                                    ));
+                                            }
+
+                                            if self.prefs.warn_far_portals
+                                                && distance_from_origin
+                                                    > self.prefs.far_portal_distance as f64
+                                            {
+                                                ui.colored_label(ui.visuals().warn_fg_color, "🌐")
+                                                    .on_hover_text(format!(
+                                                        "{distance_from_origin:.0} blocks \
+                                                         from the origin",
+                                                    ));
+                                            }
                                         },
                                     );
                                 })
                                 .body(|ui| {
                                     ui.vertical(|ui| {
-                                        portal.adjust_axis(|axis| {
-                                            ui.horizontal(|ui| {
+                                        ui.horizontal(|ui| {
+                                            let old_axis = portal.axis;
+                                            portal.adjust_axis(|axis| {
                                                 ui.label("Facing");
                                                 ui.selectable_value(axis, PortalAxis::X, "X");
                                                 ui.selectable_value(axis, PortalAxis::Z, "Z");
+                                                if ui
+                                                    .small_button("⟲")
+                                                    .on_hover_text("Rotate 90°")
+                                                    .clicked()
+                                                {
+                                                    *axis = axis.other();
+                                                }
                                             });
+                                            if self.prefs.remember_portal_axis
+                                                && portal.axis != old_axis
+                                            {
+                                                self.prefs.new_portal_axis[dimension] = portal.axis;
+                                            }
+                                            ui.weak(format!(
+                                                "(entered from {})",
+                                                portal.axis.entry_directions(),
+                                            ));
+                                            ui.separator();
+                                            ui.checkbox(&mut portal.built, "Built");
+                                            ui.separator();
+                                            if ui
+                                                .small_button("Create Linked Pair")
+                                                .on_hover_text(format!(
+                                                    "Add a portal in the {} at the \
+                                                     corresponding position",
+                                                    dimension.other(),
+                                                ))
+                                                .clicked()
+                                            {
+                                                create_linked_pair = Some(i);
+                                            }
+                                            if ui
+                                                .small_button("Save as Template")
+                                                .on_hover_text(
+                                                    "Save this portal's size, facing, color, \
+                                                     and name as a template for new portals",
+                                                )
+                                                .clicked()
+                                            {
+                                                save_as_template = Some(i);
+                                            }
                                         });
 
-                                        portal.adjust_min(
-                                            |min| show_block_pos_edit(ui, min),
-                                            self.prefs.lock_portal_size,
-                                            dimension,
-                                        );
+                                        let mut in_other_units =
+                                            self.portals_edited_in_other_units.contains(&portal.id);
+                                        if ui
+                                            .checkbox(
+                                                &mut in_other_units,
+                                                format!(
+                                                    "Edit in {} coordinates",
+                                                    dimension.other(),
+                                                ),
+                                            )
+                                            .on_hover_text(
+                                                "Show and edit the portal's coordinates \
+                                                 converted to the other dimension",
+                                            )
+                                            .changed()
+                                        {
+                                            if in_other_units {
+                                                self.portals_edited_in_other_units
+                                                    .insert(portal.id);
+                                            } else {
+                                                self.portals_edited_in_other_units
+                                                    .remove(&portal.id);
+                                            }
+                                        }
+                                        let show_pos_edit = |ui: &mut egui::Ui, pos: &mut _| {
+                                            if in_other_units {
+                                                show_converted_block_pos_edit(
+                                                    ui, pos, dimension, snap,
+                                                );
+                                            } else {
+                                                show_block_pos_edit(ui, pos, snap);
+                                            }
+                                        };
+
+                                        ui.horizontal(|ui| {
+                                            portal.adjust_min(
+                                                |min| show_pos_edit(ui, min),
+                                                self.prefs.lock_portal_size,
+                                                dimension,
+                                            );
+                                            if !IS_WEB
+                                                && ui
+                                                    .small_button("📋")
+                                                    .on_hover_text(
+                                                        "Paste coordinates, such as \
+                                                         \"x y z\" or a line from F3",
+                                                    )
+                                                    .clicked()
+                                            {
+                                                paste_coordinates = Some(portal.id);
+                                            }
+                                        });
 
                                         portal.adjust_max(
-                                            |max| show_block_pos_edit(ui, max),
+                                            |max| show_pos_edit(ui, max),
                                             self.prefs.lock_portal_size,
                                             dimension,
                                         );
 
+                                        if self.prefs.show_converted_coordinates {
+                                            let WorldRegion { min, max } = WorldRegion::from(
+                                                portal.region,
+                                            )
+                                            .convert_dimension(dimension, dimension.other());
+                                            ui.weak(format!(
+                                                "In the {}: {min} to {max}",
+                                                dimension.other(),
+                                            ))
+                                            .on_hover_text(
+                                                "Extent of the portal blocks, \
+                                                 converted to the other dimension",
+                                            );
+                                        }
+
                                         ui.horizontal(|ui| {
-                                            portal.adjust_width(|w| dv_i64(ui, "Width", w));
+                                            portal.adjust_width(|w| dv_i64(ui, "Width", w, 1));
                                             portal.adjust_height(
-                                                |h| dv_i64(ui, "Height", h),
+                                                |h| dv_i64(ui, "Height", h, 1),
                                                 dimension,
                                             );
+                                            ui.weak(format!(
+                                                "{}×{} ({} obsidian)",
+                                                portal.width(),
+                                                portal.height(),
+                                                portal.obsidian_count(),
+                                            ))
+                                            .on_hover_text(
+                                                "Obsidian needed for the frame, \
+                                                 not counting the corners",
+                                            );
+                                            if ui
+                                                .small_button("Set Corners…")
+                                                .on_hover_text(
+                                                    "Enter the coordinates of two \
+                                                     opposite corners",
+                                                )
+                                                .clicked()
+                                            {
+                                                set_corners = Some((
+                                                    portal.id,
+                                                    portal.region.min,
+                                                    portal.region.max,
+                                                ));
+                                            }
+                                        });
+
+                                        if let Some(BlockPos { x, y, z }) = portal
+                                            .destination_block(
+                                                Entity::ENDER_PEARL,
+                                                dimension.other(),
+                                            )
+                                        {
+                                            ui.weak(format!(
+                                                "Ender pearl arrives near {x}, {y}, {z} \
+                                                 in the {}",
+                                                dimension.other(),
+                                            ))
+                                            .on_hover_text(
+                                                "Center of the region where an ender pearl \
+                                                 thrown through this portal arrives",
+                                            );
+                                        }
+
+                                        if let Some((
+                                            PortalLinkResult::Portals {
+                                                new_portal: true, ..
+                                            },
+                                            _,
+                                        )) = self.cached_links.get(&portal.id)
+                                            && let Some(BlockPos { x, y, z }) = portal
+                                                .new_portal_block(
+                                                    self.prefs.entity,
+                                                    dimension.other(),
+                                                    self.prefs.new_portal_placement,
+                                                )
+                                        {
+                                            ui.weak(format!(
+                                                "New portal predicted near {x}, {y}, {z} \
+                                                 in the {}",
+                                                dimension.other(),
+                                            ));
+                                        }
+
+                                        ui.horizontal(|ui| {
+                                            let intended =
+                                                self.world.intended_links.get(&portal.id).copied();
+                                            let mut new_intended = intended;
+                                            ui.label("Intended link");
+                                            egui::ComboBox::from_id_salt((
+                                                "intended_link",
+                                                portal.id,
+                                            ))
+                                            .selected_text(
+                                                intended.map_or("None", |id| portal_name(id)),
+                                            )
+                                            .show_ui(ui, |ui| {
+                                                ui.selectable_value(
+                                                    &mut new_intended,
+                                                    None,
+                                                    "None",
+                                                );
+                                                for (id, name) in &intended_link_options {
+                                                    ui.selectable_value(
+                                                        &mut new_intended,
+                                                        Some(*id),
+                                                        name,
+                                                    );
+                                                }
+                                            })
+                                            .response
+                                            .on_hover_text(
+                                                "Portal that this portal should link to. \
+                                                 If it doesn't, the link is flagged.",
+                                            );
+                                            if new_intended != intended {
+                                                match new_intended {
+                                                    Some(id) => {
+                                                        self.world
+                                                            .intended_links
+                                                            .insert(portal.id, id);
+                                                    }
+                                                    None => {
+                                                        self.world
+                                                            .intended_links
+                                                            .remove(&portal.id);
+                                                    }
+                                                }
+                                            }
+                                            if let Some(destination) = intended
+                                                && portal_names.contains_key(&destination)
+                                                && !links_stale
+                                                && !links::is_intended_link_met(
+                                                    &self.cached_links,
+                                                    portal.id,
+                                                    destination,
+                                                )
+                                            {
+                                                ui.colored_label(
+                                                    ui.visuals().error_fg_color,
+                                                    "Not met",
+                                                );
+                                            }
                                         });
+
+                                        if let Some(shortcut) = shortcuts.get(&portal.id) {
+                                            let speed = self.prefs.travel_speed;
+                                            ui.label(
+                                                shortcut.summary(
+                                                    speed,
+                                                    portal_name(shortcut.destination),
+                                                ),
+                                            )
+                                            .on_hover_text(shortcut.time_comparison(speed));
+                                        }
                                     });
                                 });
+                                if let Some(state) = egui::collapsing_header::CollapsingState::load(
+                                    ui.ctx(),
+                                    portal_header_id(portal.id),
+                                ) {
+                                    if state.is_open() {
+                                        self.world.expanded_portals.insert(portal.id);
+                                    } else {
+                                        self.world.expanded_portals.remove(&portal.id);
+                                    }
+                                }
 
                                 show_link_result(
                                     ui,
                                     self.cached_links.get(&portal.id),
                                     &portals_by_id,
+                                    links_stale,
                                 );
+
+                                if let Some(portal_loop) = loops.get(&portal.id) {
+                                    let path = itertools::chain(portal_loop, [&portal.id])
+                                        .map(|&id| portal_name(id))
+                                        .join(" → ");
+                                    ui.colored_label(
+                                        ui.visuals().warn_fg_color,
+                                        format!("Part of a loop: {path}"),
+                                    )
+                                    .on_hover_text(
+                                        "Traveling back and forth through these portals \
+                                         does not return to where you started",
+                                    );
+                                }
                             });
 
                             reorder_drag_rect.max.y = ui.min_rect().max.y;
@@ -678,6 +1578,14 @@ impl App {
                         egui::StrokeKind::Outside,
                     );
                 }
+                if let Some(color) = self.animation_state.link_changed_color(portal.id) {
+                    ui.painter().rect_stroke(
+                        r.response.rect,
+                        4.0,
+                        (OUTLINE_WIDTH, color),
+                        egui::StrokeKind::Outside,
+                    );
+                }
                 if self.portals_hovered.in_plot.iter().exactly_one().ok() == Some(&portal.id) {
                     r.response.scroll_to_me(None);
                 }
@@ -694,6 +1602,23 @@ impl App {
             show_portal_list_contents(ui);
         }
 
+        if set_corners.is_some() {
+            self.set_corners_modal = set_corners;
+        }
+        if let Some(id) = paste_coordinates {
+            self.paste_coordinates_target = Some(id);
+            ui.ctx()
+                .send_viewport_cmd(egui::ViewportCommand::RequestPaste);
+        }
+        if let Some(i) = create_linked_pair {
+            let new_portal = self.world.portals[dimension][i].new_linked_pair(dimension);
+            self.world.portals[dimension.other()].push(new_portal);
+        }
+        if let Some(i) = save_as_template {
+            let template = PortalTemplate::from_portal(&self.world.portals[dimension][i]);
+            self.prefs.portal_templates.push(template);
+            self.templates_window_open = true;
+        }
         if let Some(i) = show_in_plot {
             self.set_camera_dimension(dimension);
             self.camera.pos = WorldRegion::from(self.world.portals[dimension][i].region).center();
@@ -710,18 +1635,32 @@ impl App {
         }
     }
 
-    fn add_portal_in_overworld(&mut self) {
-        let new_portal =
-            Portal::new_minimal(self.camera.pos.into(), PortalAxis::X, self.camera.dimension);
-        self.world.portals.overworld.push(new_portal);
+    /// Resets the camera to view the portals in the current dimension.
+    fn reset_camera(&mut self) {
+        let dimension = self.camera.dimension;
+        let region = self.world.portals[dimension]
+            .iter()
+            .map(|p| WorldRegion::from(p.region))
+            .reduce(WorldRegion::union);
+        self.camera.reset_to_region(dimension, region);
+    }
+
+    fn add_portal(&mut self, dimension: Dimension) {
+        self.add_portal_at(dimension, self.camera.pos_in(dimension));
     }
-    fn add_portal_in_nether(&mut self) {
-        let new_portal = Portal::new_minimal(
-            self.camera.pos.overworld_to_nether().into(),
-            PortalAxis::X,
-            self.camera.dimension,
-        );
-        self.world.portals.nether.push(new_portal);
+    fn add_portal_at(&mut self, dimension: Dimension, pos: WorldPos) {
+        let mut new_portal =
+            Portal::new_minimal(pos.into(), self.prefs.new_portal_axis[dimension], dimension);
+        let [width, height] = self.prefs.new_portal_size;
+        new_portal.adjust_width(|w| *w = width);
+        new_portal.adjust_height(|h| *h = height, dimension);
+        self.world.portals[dimension].push(new_portal);
+    }
+    fn add_portal_from_template(&mut self, dimension: Dimension, template: &PortalTemplate) {
+        let n = self.world.portals[dimension].len() + 1;
+        let pos = self.camera.pos_in(dimension).into();
+        let new_portal = template.instantiate(pos, dimension, n);
+        self.world.portals[dimension].push(new_portal);
     }
 
     fn show_view(
@@ -730,6 +1669,7 @@ impl App {
         plane: Plane,
         new_camera: &mut Camera,
     ) -> egui::Response {
+        let z_up = self.prefs.positive_z_up;
         let aspect_ratio_scale = self.animation_state.aspect_ratio_scale;
         let width_scale = 1.0;
         let height_scale = match plane {
@@ -755,111 +1695,386 @@ impl App {
             .x_axis_formatter(|mark, _range| mark.value.to_string())
             .y_axis_formatter(|mark, _range| {
                 let y = mark.value;
-                if Plane::XZ == plane { -y } else { y }.to_string()
+                if Plane::XZ == plane {
+                    y * Plane::z_sign(z_up)
+                } else {
+                    y
+                }
+                .to_string()
             })
             .allow_axis_zoom_drag(false)
+            .allow_double_click_reset(false)
             .allow_boxed_zoom(false)
             .show_x(false)
             .show_y(false)
             .coordinates_formatter(
                 egui_plot::Corner::LeftBottom,
                 egui_plot::CoordinatesFormatter::new(|hover_point, _bounds| {
-                    let pos = plane.plot_to_world(*hover_point, *new_camera);
+                    let pos = plane.plot_to_world(*hover_point, *new_camera, z_up);
+                    let overworld = pos.convert_dimension(self.camera.dimension, Overworld);
+                    let nether = pos.convert_dimension(self.camera.dimension, Nether);
                     format!(
-                        "Overworld: {overworld:10.03}\n   Nether: {nether:10.03}",
-                        overworld = pos.convert_dimension(self.camera.dimension, Overworld),
-                        nether = pos.convert_dimension(self.camera.dimension, Nether),
+                        "Overworld: {overworld:10.03}\n           {overworld_block:10}\n   \
+                         Nether: {nether:10.03}\n           {nether_block:10}",
+                        overworld_block = BlockPos::from(overworld),
+                        nether_block = BlockPos::from(nether),
                     )
                 }),
             );
 
+        // Dragging a selected portal moves it instead of panning.
+        let hovering_selected =
+            (self.portals_hovered.in_plot.iter()).any(|id| self.selected_portals.contains(id));
+        plot = plot.allow_drag(self.portal_drag.is_none() && !hovering_selected);
+
         plot = match plane {
             Plane::XY => plot,
             Plane::XZ => plot.x_axis_position(egui_plot::VPlacement::Top),
             Plane::ZY => plot.y_axis_position(egui_plot::HPlacement::Right),
         };
 
+        let painter = ui.painter().clone();
         let r = plot.show(ui, |plot_ui| {
             // Compute plot bounds from camera
             let mut bounds_from_camera = egui_plot::PlotBounds::NOTHING;
-            let egui_plot::PlotPoint { x, y } = plane.world_to_plot(self.camera.pos);
+            let egui_plot::PlotPoint { x, y } = plane.world_to_plot(self.camera.pos, z_up);
             let raw_size = plot_ui.transform().frame().size();
             let new_width = self.camera.height * raw_size.x as f64 / raw_size.y as f64;
             bounds_from_camera.set_x_center_width(x, new_width * width_scale);
             bounds_from_camera.set_y_center_height(y, self.camera.height * height_scale);
 
             plot_ui.set_plot_bounds(bounds_from_camera);
+            let points_per_value = raw_size
+                / egui::vec2(
+                    bounds_from_camera.width() as f32,
+                    bounds_from_camera.height() as f32,
+                );
 
-            self.show_portals_in_plot(plot_ui, plane);
+            self.show_areas_in_plot(plot_ui, plane);
+            self.show_portals_in_plot(plot_ui, plane, &painter, points_per_value);
             self.show_portal_connections_in_plot(plot_ui, plane);
+            self.show_intended_links_in_plot(plot_ui, plane);
+            if self.prefs.preview_links_at_cursor
+                && let Some((dim, pos)) = self.link_preview
+            {
+                self.show_link_preview_in_plot(plot_ui, plane, dim, pos);
+            }
+            if self.prefs.preview_links_at_camera {
+                let Camera { dimension, pos, .. } = self.camera;
+                self.show_link_preview_in_plot(plot_ui, plane, dimension, pos);
+            }
             self.show_test_points_in_plot(plot_ui, plane);
+            self.show_routes_in_plot(plot_ui, plane);
+            self.show_comparison_in_plot(plot_ui, plane);
+            #[cfg(not(target_arch = "wasm32"))]
+            self.show_live_position_in_plot(plot_ui, plane);
+            if self.prefs.show_crosshair {
+                show_crosshair_in_plot(plot_ui, egui_plot::PlotPoint { x, y });
+            }
         });
 
+        show_scale_bar(ui, *r.transform.frame(), r.transform.bounds().width());
+
+        let minimap_hovered = plane == Plane::XZ
+            && self.prefs.show_minimap
+            && self.show_minimap(ui, *r.transform.frame(), *r.transform.bounds(), new_camera);
+
         if let Some(hovered_world_pos) = r
             .response
             .hover_pos()
+            .filter(|_| !minimap_hovered)
             .filter(|&pos| r.transform.frame().contains(pos))
             .map(|pos| r.transform.value_from_position(pos))
-            .map(|point| plane.plot_to_world(point, *new_camera))
+            .map(|point| plane.plot_to_world(point, *new_camera, z_up))
         {
+            let mut hovered_portals =
+                self.portals_at(new_camera.dimension, plane, hovered_world_pos);
             if self.prefs.hover_either_dimension {
-                self.process_portal_hovers(Overworld, plane, hovered_world_pos);
-                self.process_portal_hovers(Nether, plane, hovered_world_pos);
-            } else {
-                self.process_portal_hovers(new_camera.dimension, plane, hovered_world_pos);
+                hovered_portals.extend(self.portals_at(
+                    new_camera.dimension.other(),
+                    plane,
+                    hovered_world_pos,
+                ));
+            }
+            // The second click of a double-click only adds a portal.
+            if r.response.double_clicked() {
+                if self.drawing_route.is_none() && hovered_portals.is_empty() {
+                    self.add_portal_at(new_camera.dimension, hovered_world_pos);
+                }
+            } else if r.response.clicked() {
+                if let Some((dim, i)) = self.drawing_route
+                    && let Some(route) = self.world.routes[dim].get_mut(i)
+                {
+                    let point = hovered_world_pos.convert_dimension(new_camera.dimension, dim);
+                    route.points.push(point);
+                } else {
+                    self.click_portals_in_plot(ui, &hovered_portals);
+                }
+            }
+            if r.response.secondary_clicked() {
+                self.plot_context_menu_portal = hovered_portals.first().copied();
+            }
+            if hovered_portals.is_empty() && self.drawing_route.is_none() {
+                self.link_preview_for_next_frame = Some((new_camera.dimension, hovered_world_pos));
             }
+            self.portals_hovered
+                .in_plot_for_next_frame
+                .extend(hovered_portals);
         }
 
+        self.drag_portals_in_plot(ui, &r, plane, *new_camera, hovering_selected);
+
+        r.response
+            .context_menu(|ui| self.show_plot_context_menu(ui));
+
         // Update camera on interaction with plot
-        if r.response.hovered() || r.response.dragged() {
+        if !minimap_hovered && (r.response.hovered() || r.response.dragged()) {
             let bounds = r.transform.bounds();
             let egui_plot::PlotPoint { x, y } = bounds.center();
             match plane {
                 Plane::XY => (new_camera.pos.x, new_camera.pos.y) = (x, y),
-                Plane::XZ => (new_camera.pos.x, new_camera.pos.z) = (x, -y),
+                Plane::XZ => (new_camera.pos.x, new_camera.pos.z) = (x, y * Plane::z_sign(z_up)),
                 Plane::ZY => (new_camera.pos.z, new_camera.pos.y) = (x, y),
             }
             new_camera.width = bounds.width() / width_scale;
             new_camera.height = bounds.height() / height_scale;
         }
 
-        r.response
-    }
-
-    fn show_portals_in_plot(&self, plot_ui: &mut egui_plot::PlotUi<'_>, plane: Plane) {
-        let dimension = self.camera.dimension;
-        for portal_dim in [dimension, dimension.other()] {
-            for portal in &self.world.portals[portal_dim] {
-                self.show_portal_in_plot(plot_ui, plane, portal, portal_dim, dimension);
+        // Pan with the middle mouse button, even over selected portals
+        if r.response.dragged_by(egui::PointerButton::Middle) {
+            ui.ctx().set_cursor_icon(egui::CursorIcon::Grabbing);
+            let center = r.transform.frame().center();
+            let point = r
+                .transform
+                .value_from_position(center - r.response.drag_delta());
+            let pos = plane.plot_to_world(point, *new_camera, z_up);
+            for axis in plane.axes() {
+                new_camera.pos[axis] = pos[axis];
             }
         }
+
+        r.response
     }
 
-    fn show_portal_in_plot(
-        &self,
-        plot_ui: &mut egui_plot::PlotUi<'_>,
+    /// Moves the selected portals when they are dragged in the plot.
+    ///
+    /// Only the axes visible in `plane` are changed. Holding shift constrains
+    /// the movement to a single axis, and holding Alt snaps the movement to
+    /// multiples of the snap increment.
+    fn drag_portals_in_plot(
+        &mut self,
+        ui: &egui::Ui,
+        r: &egui_plot::PlotResponse<()>,
         plane: Plane,
-        portal: &Portal,
-        portal_dimension: Dimension,
-        plot_dimension: Dimension,
+        camera: Camera,
+        hovering_selected: bool,
     ) {
-        let opacity = if portal_dimension == plot_dimension {
-            1.0
-        } else {
-            0.5
-        };
-        let stroke_width = if portal_dimension == plot_dimension {
-            3.0
-        } else {
-            1.5
+        let z_up = self.prefs.positive_z_up;
+        let to_world =
+            |pos| plane.plot_to_world(r.transform.value_from_position(pos), camera, z_up);
+        let Some(pointer_pos) = r.response.interact_pointer_pos().map(to_world) else {
+            return;
         };
 
-        let region =
-            WorldRegion::from(portal.region).convert_dimension(portal_dimension, plot_dimension);
-
-        let a = plane.world_to_plot(region.min);
-        let b = plane.world_to_plot(region.max);
-        let points = vec![[a.x, a.y], [a.x, b.y], [b.x, b.y], [b.x, a.y]];
+        if r.response.drag_started()
+            && hovering_selected
+            && let Some(press_origin) = ui.input(|input| input.pointer.press_origin())
+        {
+            let portals = (self.selected_portals.iter())
+                .filter_map(|&id| {
+                    let (dim, i) = self.portal_index(id)?;
+                    Some((dim, id, self.world.portals[dim][i].region))
+                })
+                .collect();
+            self.portal_drag = Some(PortalDrag {
+                plane,
+                start: to_world(press_origin),
+                portals,
+            });
+        }
+
+        let Some(drag) = self.portal_drag.clone().filter(|d| d.plane == plane) else {
+            return;
+        };
+        if r.response.drag_stopped() {
+            self.portal_drag = None;
+            return;
+        }
+        if !r.response.dragged() {
+            return;
+        }
+
+        let [h, v] = plane.axes();
+        let total = pointer_pos - drag.start;
+        let mut delta = WorldPos::default();
+        delta[h] = total[h];
+        delta[v] = total[v];
+        if ui.input(|input| input.modifiers.shift) {
+            if delta[h].abs() < delta[v].abs() {
+                delta[h] = 0.0;
+            } else {
+                delta[v] = 0.0;
+            }
+        }
+
+        let snap = ui.input(|input| input.modifiers.alt);
+        for &(dim, id, original_region) in &drag.portals {
+            let mut offset = delta.convert_dimension(camera.dimension, dim).round();
+            if snap {
+                for axis in [h, v] {
+                    offset[axis] = snap_to_multiple(offset[axis], self.prefs.snap_increment);
+                }
+            }
+            if let Some((_, i)) = self.portal_index(id) {
+                let portal = &mut self.world.portals[dim][i];
+                portal.region = original_region;
+                portal.translate(offset, dim);
+            }
+        }
+    }
+
+    /// Shows an overview of the whole world in the corner of the XZ plot,
+    /// with a rectangle for the viewport. Clicking or dragging on it moves the
+    /// camera.
+    ///
+    /// Returns whether the minimap is hovered.
+    fn show_minimap(
+        &self,
+        ui: &mut egui::Ui,
+        plot_frame: egui::Rect,
+        viewport: egui_plot::PlotBounds,
+        new_camera: &mut Camera,
+    ) -> bool {
+        let z_up = self.prefs.positive_z_up;
+        const MAX_SIZE: f32 = 160.0;
+
+        let plane = Plane::XZ;
+        let Some(world_region) = self.world.bounding_region(self.camera.dimension) else {
+            return false;
+        };
+        let a = plane.world_to_plot(world_region.min, z_up);
+        let b = plane.world_to_plot(world_region.max, z_up);
+        let [viewport_min, viewport_max] = [viewport.min(), viewport.max()];
+        let world_is_visible = viewport_min[0] <= a.x.min(b.x)
+            && viewport_min[1] <= a.y.min(b.y)
+            && b.x.max(a.x) <= viewport_max[0]
+            && b.y.max(a.y) <= viewport_max[1];
+        if world_is_visible {
+            return false;
+        }
+
+        // Plot bounds of the whole minimap
+        let mut extent = viewport;
+        extent.extend_with(&a);
+        extent.extend_with(&b);
+        let scale = MAX_SIZE / extent.width().max(extent.height()) as f32;
+        let size = egui::vec2(extent.width() as f32, extent.height() as f32) * scale;
+        let rect = egui::Rect::from_min_size(
+            plot_frame.right_bottom() - size - egui::vec2(PLOT_MARGIN, PLOT_MARGIN),
+            size,
+        );
+        let plot_to_screen = |p: egui_plot::PlotPoint| {
+            egui::pos2(
+                rect.min.x + (p.x - extent.min()[0]) as f32 * scale,
+                rect.max.y - (p.y - extent.min()[1]) as f32 * scale,
+            )
+        };
+        let screen_to_plot = |p: egui::Pos2| egui_plot::PlotPoint {
+            x: extent.min()[0] + ((p.x - rect.min.x) / scale) as f64,
+            y: extent.min()[1] + ((rect.max.y - p.y) / scale) as f64,
+        };
+
+        let r = ui.interact(rect, ui.id().with("minimap"), egui::Sense::click_and_drag());
+        if let Some(pointer) = r.interact_pointer_pos()
+            && (r.clicked() || r.dragged())
+        {
+            let world_pos = plane.plot_to_world(screen_to_plot(pointer), *new_camera, z_up);
+            new_camera.pos.x = world_pos.x;
+            new_camera.pos.z = world_pos.z;
+        }
+
+        let painter = ui.painter_at(rect);
+        let visuals = ui.visuals();
+        painter.rect_filled(rect, 2.0, visuals.extreme_bg_color.gamma_multiply(0.8));
+        for dim in [Overworld, Nether] {
+            for portal in
+                (self.world.portals[dim].iter()).filter(|p| !self.hidden_portals.contains(&p.id))
+            {
+                let region =
+                    WorldRegion::from(portal.region).convert_dimension(dim, self.camera.dimension);
+                let portal_rect = egui::Rect::from_two_pos(
+                    plot_to_screen(plane.world_to_plot(region.min, z_up)),
+                    plot_to_screen(plane.world_to_plot(region.max, z_up)),
+                )
+                .expand(1.0);
+                let [r, g, b] = portal.color;
+                painter.rect_filled(portal_rect, 0.0, egui::Color32::from_rgb(r, g, b));
+            }
+        }
+        let viewport_rect = egui::Rect::from_two_pos(
+            plot_to_screen(egui_plot::PlotPoint::from(viewport_min)),
+            plot_to_screen(egui_plot::PlotPoint::from(viewport_max)),
+        );
+        painter.rect_stroke(
+            viewport_rect,
+            0.0,
+            visuals.widgets.active.fg_stroke,
+            egui::StrokeKind::Inside,
+        );
+        painter.rect_stroke(rect, 2.0, visuals.window_stroke, egui::StrokeKind::Inside);
+
+        r.hovered() || r.dragged()
+    }
+
+    fn show_portals_in_plot(
+        &self,
+        plot_ui: &mut egui_plot::PlotUi<'_>,
+        plane: Plane,
+        painter: &egui::Painter,
+        points_per_value: egui::Vec2,
+    ) {
+        let dimension = self.camera.dimension;
+        let mut labels = vec![];
+        for portal_dim in [dimension, dimension.other()] {
+            for portal in self.world.portals[portal_dim].iter() {
+                if !self.is_shown_in_plot(portal) {
+                    continue;
+                }
+                labels.extend(
+                    self.show_portal_in_plot(plot_ui, plane, portal, portal_dim, dimension),
+                );
+            }
+        }
+        show_labels_in_plot(plot_ui, labels, painter, points_per_value);
+    }
+
+    fn show_portal_in_plot(
+        &self,
+        plot_ui: &mut egui_plot::PlotUi<'_>,
+        plane: Plane,
+        portal: &Portal,
+        portal_dimension: Dimension,
+        plot_dimension: Dimension,
+    ) -> Option<(egui_plot::PlotPoint, egui::text::LayoutJob)> {
+        let z_up = self.prefs.positive_z_up;
+        let opacity = if portal_dimension == plot_dimension {
+            1.0
+        } else {
+            0.5
+        };
+        let stroke_width = if portal_dimension == plot_dimension {
+            3.0
+        } else {
+            1.5
+        };
+
+        let region =
+            WorldRegion::from(portal.region).convert_dimension(portal_dimension, plot_dimension);
+
+        let a = plane.world_to_plot(region.min, z_up);
+        let b = plane.world_to_plot(region.max, z_up);
+        let points = vec![[a.x, a.y], [a.x, b.y], [b.x, b.y], [b.x, a.y]];
+        let outline = points.clone();
 
         let base_color = match portal_dimension {
             Overworld => egui::Color32::BLUE,
@@ -880,8 +2095,44 @@ impl App {
 
         plot_ui.add(polygon);
 
+        if let Some(color) = self.animation_state.link_changed_color(portal.id) {
+            plot_ui.add(
+                egui_plot::Polygon::new("", outline.clone())
+                    .fill_color(egui::Color32::TRANSPARENT)
+                    .stroke((stroke_width + 2.0, color.gamma_multiply(opacity))),
+            );
+        }
+
+        if self.is_near_go_to_target(portal, portal_dimension) {
+            plot_ui.add(
+                egui_plot::Polygon::new("", outline.clone())
+                    .fill_color(egui::Color32::TRANSPARENT)
+                    .stroke((
+                        stroke_width + 2.0,
+                        egui::Color32::YELLOW.gamma_multiply(opacity),
+                    )),
+            );
+        }
+
+        if self.selected_portals.contains(&portal.id) {
+            plot_ui.add(
+                egui_plot::Polygon::new("", outline)
+                    .fill_color(egui::Color32::TRANSPARENT)
+                    .stroke((1.5, egui::Color32::WHITE.gamma_multiply(opacity)))
+                    .style(egui_plot::LineStyle::dashed_dense()),
+            );
+        }
+
         if self.portals_hovered.contains(portal.id) {
-            if let Some(region) = portal.entity_collision_region(self.prefs.entity) {
+            let other_entities = std::iter::zip(ENTITY_PRESETS, ENTITY_OUTLINE_COLORS)
+                .filter(|((name, _), _)| self.prefs.outline_entities.iter().any(|n| n == name))
+                .map(|((_, entity), color)| (entity, color));
+            let entities =
+                itertools::chain([(self.prefs.entity, egui::Color32::WHITE)], other_entities);
+            for (entity, color) in entities {
+                let Ok(region) = portal.try_entity_collision_region(entity) else {
+                    continue;
+                };
                 let region = WorldRegion::from(
                     region
                         .convert_dimension(portal_dimension, portal_dimension.other())
@@ -889,77 +2140,105 @@ impl App {
                 )
                 .convert_dimension(portal_dimension.other(), plot_dimension);
 
-                let a = plane.world_to_plot(region.min);
-                let b = plane.world_to_plot(region.max);
+                let a = plane.world_to_plot(region.min, z_up);
+                let b = plane.world_to_plot(region.max, z_up);
                 let points = vec![[a.x, a.y], [a.x, b.y], [b.x, b.y], [b.x, a.y]];
 
-                plot_ui
-                    .add(egui_plot::Polygon::new("", points).stroke((1.0, egui::Color32::WHITE)));
+                plot_ui.add(egui_plot::Polygon::new("", points).stroke((1.0, color)));
+            }
+
+            if plane == Plane::XZ {
+                // Show which way the portal is entered.
+                let center = region.center();
+                let d = portal.depth_axis();
+                let half_length =
+                    (region.max[portal.width_axis()] - region.min[portal.width_axis()]) / 2.0;
+                let mut tips = [center; 2];
+                tips[0][d] -= half_length;
+                tips[1][d] += half_length;
+                plot_ui.add(
+                    egui_plot::Arrows::new(
+                        "",
+                        egui_plot::PlotPoints::Owned(vec![plane.world_to_plot(center, z_up); 2]),
+                        egui_plot::PlotPoints::Owned(
+                            tips.map(|tip| plane.world_to_plot(tip, z_up)).to_vec(),
+                        ),
+                    )
+                    .color(egui::Color32::WHITE),
+                );
             }
         }
 
-        if !portal.name.is_empty() {
-            let mut job = egui::text::LayoutJob::default();
-            job.append(
-                if self.prefs.show_all_labels || self.portals_hovered.contains(portal.id) {
-                    &portal.name
-                } else {
-                    ""
-                },
-                0.0,
-                egui::TextFormat::simple(
-                    egui::FontId::proportional(14.0),
-                    stroke_color.linear_multiply(0.25).additive()
-                        + egui::Color32::WHITE.linear_multiply(0.75).additive(),
-                ),
+        let show_label = self.show_all_labels() || self.portals_hovered.contains(portal.id);
+        (show_label && !portal.name.is_empty()).then(|| {
+            let job = egui::text::LayoutJob::simple_singleline(
+                portal.name.clone(),
+                egui::FontId::proportional(14.0),
+                stroke_color.linear_multiply(0.25).additive()
+                    + egui::Color32::WHITE.linear_multiply(0.75).additive(),
             );
-
-            plot_ui.add(egui_plot::Text::new(
-                "",
-                plane.world_to_plot(region.center()),
-                job,
-            ));
-        }
+            (plane.world_to_plot(region.center(), z_up), job)
+        })
     }
 
     fn show_portal_connections_in_plot(&self, plot_ui: &mut egui_plot::PlotUi<'_>, plane: Plane) {
-        if !self.prefs.show_all_arrows && self.portals_hovered.is_empty() {
+        let show_all_arrows = self.show_all_arrows();
+        if !show_all_arrows && self.portals_hovered.is_empty() && self.solo_portal.is_none() {
             return;
         }
 
-        let id_to_portal: HashMap<PortalId, &Portal> =
-            itertools::chain(&self.world.portals.overworld, &self.world.portals.nether)
-                .map(|p| (p.id, p))
-                .collect();
-        let overworld_portal_set: HashSet<PortalId> =
-            self.world.portals.overworld.iter().map(|p| p.id).collect();
-        let get_dim_of_portal = |id| {
-            if overworld_portal_set.contains(id) {
-                Overworld
-            } else {
-                Nether
-            }
+        let get_portal = |id: PortalId| {
+            self.portal_lookup
+                .get(&self.world.portals, id)
+                .filter(|(_, p)| self.is_shown_in_plot(p))
+        };
+        let stale = self.links_stale;
+        let is_focused =
+            |id: PortalId| self.portals_hovered.contains(id) || self.solo_portal == Some(id);
+        let direction = self.prefs.focused_link_direction;
+        let is_arrow_shown = |src: PortalId, dst: PortalId| {
+            show_all_arrows
+                || (direction.includes_outgoing() && is_focused(src))
+                || (direction.includes_incoming() && is_focused(dst))
+        };
+        let outgoing_ids = |id: PortalId| match self.cached_links.get(&id) {
+            Some((PortalLinkResult::Portals { ids, .. }, _)) => ids.as_slice(),
+            _ => &[],
         };
         for (id2, (_outgoing, incoming)) in &self.cached_links {
-            let Some(portal2) = id_to_portal.get(id2) else {
+            let Some((dim2, portal2)) = get_portal(*id2) else {
                 continue;
             };
-            let dim2 = get_dim_of_portal(id2);
 
             for id1 in incoming {
-                if self.prefs.show_all_arrows
-                    || self.portals_hovered.contains(*id1)
-                    || self.portals_hovered.contains(*id2)
-                {
-                    let Some(portal1) = id_to_portal.get(id1) else {
-                        continue;
-                    };
-                    let dim1 = get_dim_of_portal(id1);
-
-                    self.show_portal_connection_in_plot(
-                        plot_ui, plane, portal1, dim1, portal2, dim2,
-                    );
+                if !is_arrow_shown(*id1, *id2) {
+                    continue;
+                }
+                // Draw links in both directions as a single double-headed
+                // arrow.
+                let bidirectional = outgoing_ids(*id2).contains(id1) && is_arrow_shown(*id2, *id1);
+                if bidirectional && id1 > id2 {
+                    continue; // drawn when iterating over the other portal
+                }
+                let mut ambiguous = outgoing_ids(*id1).len() > 1;
+                if bidirectional {
+                    ambiguous |= outgoing_ids(*id2).len() > 1;
                 }
+                let Some((dim1, portal1)) = get_portal(*id1) else {
+                    continue;
+                };
+
+                self.show_portal_connection_in_plot(
+                    plot_ui,
+                    plane,
+                    portal1,
+                    dim1,
+                    portal2,
+                    dim2,
+                    ambiguous,
+                    bidirectional,
+                    stale,
+                );
             }
         }
     }
@@ -978,47 +2257,372 @@ impl App {
         src_dimension: Dimension,
         dst: &Portal,
         dst_dimension: Dimension,
+        ambiguous: bool,
+        bidirectional: bool,
+        stale: bool,
+    ) {
+        let (src_point, dst_point) =
+            self.connection_endpoints(plot_ui, plane, src, src_dimension, dst, dst_dimension);
+        let may_generate_new_portal = |p: &Portal| {
+            matches!(
+                self.cached_links.get(&p.id),
+                Some((
+                    PortalLinkResult::Portals {
+                        new_portal: true,
+                        ..
+                    },
+                    _
+                )),
+            )
+        };
+
+        let mut color = match self.arrow_coloring() {
+            ArrowColoring::BySource => {
+                egui::Color32::from_rgb(src.color[0], src.color[1], src.color[2])
+            }
+            ArrowColoring::ByDestination => {
+                egui::Color32::from_rgb(dst.color[0], dst.color[1], dst.color[2])
+            }
+            ArrowColoring::ByDimension => DIMENSION_ARROW_COLORS[dst_dimension],
+            ArrowColoring::ByQuality => {
+                if may_generate_new_portal(src) || (bidirectional && may_generate_new_portal(dst)) {
+                    LINK_NEW_PORTAL_COLOR
+                } else if ambiguous {
+                    LINK_AMBIGUOUS_COLOR
+                } else {
+                    LINK_OK_COLOR
+                }
+            }
+        };
+        if ambiguous {
+            color = color.gamma_multiply(0.6);
+        }
+        if stale {
+            color = color.gamma_multiply(0.3);
+        }
+
+        let entities_note = |a: &Portal, b: &Portal| {
+            let entities = self
+                .cached_link_entities
+                .get(&a.id)?
+                .get(&b.id)
+                .map_or(&[][..], |v| v.as_slice());
+            if entities.len() == real_entity_presets().count() {
+                None
+            } else if entities.is_empty() {
+                Some("No preset entities take this link".to_owned())
+            } else {
+                Some(format!("Only for: {}", entities.join(", ")))
+            }
+        };
+
+        if bidirectional {
+            let mut name = format!(
+                "{} and {} link to each other",
+                src.display_name(),
+                dst.display_name()
+            );
+            for (a, b) in [(src, dst), (dst, src)] {
+                if let Some(note) = entities_note(a, b) {
+                    name += &format!("\n{} to {}: {note}", a.display_name(), b.display_name());
+                }
+            }
+            let mid_point = egui_plot::PlotPoint::new(
+                (src_point.x + dst_point.x) / 2.0,
+                (src_point.y + dst_point.y) / 2.0,
+            );
+            self.show_arrow_in_plot(
+                plot_ui,
+                name.clone(),
+                mid_point,
+                dst_point,
+                color,
+                ambiguous,
+            );
+            self.show_arrow_in_plot(plot_ui, name, mid_point, src_point, color, ambiguous);
+        } else {
+            let mut name = format!("{} to {}", src.display_name(), dst.display_name());
+            if let Some(note) = entities_note(src, dst) {
+                name += &format!("\n{note}");
+            }
+            self.show_arrow_in_plot(plot_ui, name, src_point, dst_point, color, ambiguous);
+        }
+    }
+
+    /// Shows a red dashed arrow for each intended link that is not met.
+    fn show_intended_links_in_plot(&self, plot_ui: &mut egui_plot::PlotUi<'_>, plane: Plane) {
+        if self.links_stale {
+            return;
+        }
+        for (src_id, dst_id) in links::unmet_intended_links(&self.world, &self.cached_links) {
+            let (Some((src_dim, i)), Some((dst_dim, j))) =
+                (self.portal_index(src_id), self.portal_index(dst_id))
+            else {
+                continue;
+            };
+            let src = &self.world.portals[src_dim][i];
+            let dst = &self.world.portals[dst_dim][j];
+            if !self.is_shown_in_plot(src) || !self.is_shown_in_plot(dst) {
+                continue;
+            }
+            let (src_point, dst_point) =
+                self.connection_endpoints(plot_ui, plane, src, src_dim, dst, dst_dim);
+            let name = format!(
+                "{} should link to {}",
+                src.display_name(),
+                dst.display_name(),
+            );
+            self.show_arrow_in_plot(
+                plot_ui,
+                name,
+                src_point,
+                dst_point,
+                UNMET_INTENDED_LINK_COLOR,
+                true,
+            );
+        }
+    }
+
+    /// Shows arrows from `pos` in `dim`, such as the empty position under the
+    /// cursor, to the portals that an entity arriving there would link to.
+    fn show_link_preview_in_plot(
+        &self,
+        plot_ui: &mut egui_plot::PlotUi<'_>,
+        plane: Plane,
+        dim: Dimension,
+        pos: WorldPos,
     ) {
+        let z_up = self.prefs.positive_z_up;
+        let camera_dim = self.camera.dimension;
+        let src_point = plane.world_to_plot(pos.convert_dimension(dim, camera_dim), z_up);
+        let destinations = self.world.portals.entity_destinations(dim, pos);
+        if destinations.is_empty() {
+            plot_ui.add(
+                egui_plot::Points::new("", egui_plot::PlotPoints::Owned(vec![src_point]))
+                    .shape(egui_plot::MarkerShape::Cross)
+                    .radius(CROSSHAIR_RADIUS)
+                    .color(egui::Color32::RED.gamma_multiply(0.6)),
+            );
+        }
+        let ambiguous = destinations.len() > 1;
+        for dst in destinations
+            .into_iter()
+            .filter(|p| self.is_shown_in_plot(p))
+        {
+            let dst_pos = WorldRegion::from(dst.region)
+                .center()
+                .convert_dimension(dim.other(), camera_dim);
+            let [r, g, b] = dst.color;
+            self.show_arrow_in_plot(
+                plot_ui,
+                format!("Links to {}", dst.display_name()),
+                src_point,
+                plane.world_to_plot(dst_pos, z_up),
+                egui::Color32::from_rgb(r, g, b).gamma_multiply(0.6),
+                ambiguous,
+            );
+        }
+    }
+
+    /// Returns the start and end of an arrow between two portals, in plot
+    /// coordinates.
+    fn connection_endpoints(
+        &self,
+        plot_ui: &mut egui_plot::PlotUi<'_>,
+        plane: Plane,
+        src: &Portal,
+        src_dimension: Dimension,
+        dst: &Portal,
+        dst_dimension: Dimension,
+    ) -> (egui_plot::PlotPoint, egui_plot::PlotPoint) {
+        let z_up = self.prefs.positive_z_up;
         let camera_dim = self.camera.dimension;
         let src_pos = WorldRegion::from(src.region).center();
         let dst_pos = WorldRegion::from(dst.region).center();
         let mut src_point =
-            plane.world_to_plot(src_pos.convert_dimension(src_dimension, camera_dim));
+            plane.world_to_plot(src_pos.convert_dimension(src_dimension, camera_dim), z_up);
         let mut dst_point =
-            plane.world_to_plot(dst_pos.convert_dimension(dst_dimension, camera_dim));
+            plane.world_to_plot(dst_pos.convert_dimension(dst_dimension, camera_dim), z_up);
 
         let dpos_dvalue_x = self.dpos_dvalue_x(plot_ui);
 
-        // Shrink arrow by half a block
-        let vector =
-            (dst_point.to_pos2() - src_point.to_pos2()).normalized() * 8.0 / dpos_dvalue_x.sqrt();
-        src_point.x += vector.x as f64;
-        src_point.y += vector.y as f64;
-        dst_point.x -= vector.x as f64;
-        dst_point.y -= vector.y as f64;
-
-        let [r, g, b] = match self.prefs.arrow_coloring {
-            ArrowColoring::BySource => src.color,
-            ArrowColoring::ByDestination => dst.color,
+        // Shrink arrow by half a block, unless that would flip its direction
+        let delta = dst_point.to_pos2() - src_point.to_pos2();
+        let vector = delta.normalized() * 8.0 / dpos_dvalue_x.sqrt();
+        if delta.length() > vector.length() * 2.0 {
+            src_point.x += vector.x as f64;
+            src_point.y += vector.y as f64;
+            dst_point.x -= vector.x as f64;
+            dst_point.y -= vector.y as f64;
+        }
+
+        (src_point, dst_point)
+    }
+
+    /// Shows an arrow in the plot, which may be dashed with only the tip of
+    /// the arrow at the end.
+    fn show_arrow_in_plot(
+        &self,
+        plot_ui: &mut egui_plot::PlotUi<'_>,
+        name: String,
+        src_point: egui_plot::PlotPoint,
+        dst_point: egui_plot::PlotPoint,
+        color: egui::Color32,
+        dashed: bool,
+    ) {
+        let camera_dim = self.camera.dimension;
+        let dpos_dvalue_x = self.dpos_dvalue_x(plot_ui);
+        let arrow_start = if dashed {
+            // Draw a dashed line, with only the tip of the arrow at the end.
+            plot_ui.add(
+                egui_plot::Line::new(
+                    name.clone(),
+                    vec![[src_point.x, src_point.y], [dst_point.x, dst_point.y]],
+                )
+                .color(color)
+                .style(egui_plot::LineStyle::dashed_loose()),
+            );
+            egui_plot::PlotPoint::new(
+                dst_point.x - (dst_point.x - src_point.x) * 0.001,
+                dst_point.y - (dst_point.y - src_point.y) * 0.001,
+            )
+        } else {
+            src_point
         };
 
         plot_ui.add(
             egui_plot::Arrows::new(
-                format!("{} to {}", src.display_name(), dst.display_name()),
-                egui_plot::PlotPoints::Owned(vec![src_point]),
+                name,
+                egui_plot::PlotPoints::Owned(vec![arrow_start]),
                 egui_plot::PlotPoints::Owned(vec![dst_point]),
             )
-            .color(egui::Color32::from_rgb(r, g, b))
-            .tip_length(dpos_dvalue_x.sqrt() / camera_dim.scale() as f32 * 6.0),
+            .color(color)
+            .tip_length(clamp_to_range(
+                dpos_dvalue_x.sqrt() / camera_dim.scale() as f32 * 6.0,
+                ARROW_TIP_LENGTH_RANGE,
+            )),
         );
     }
 
+    fn show_areas_in_plot(&self, plot_ui: &mut egui_plot::PlotUi<'_>, plane: Plane) {
+        let z_up = self.prefs.positive_z_up;
+        let camera_dim = self.camera.dimension;
+        for dim in [camera_dim.other(), camera_dim] {
+            let opacity = if dim == camera_dim { 1.0 } else { 0.5 };
+            for area in &self.world.areas[dim] {
+                let region = WorldRegion::from(area.region).convert_dimension(dim, camera_dim);
+                let a = plane.world_to_plot(region.min, z_up);
+                let b = plane.world_to_plot(region.max, z_up);
+                let points = vec![[a.x, a.y], [a.x, b.y], [b.x, b.y], [b.x, a.y]];
+                let [r, g, b] = area.color;
+                let color = egui::Color32::from_rgb(r, g, b).gamma_multiply(opacity);
+                plot_ui.add(
+                    egui_plot::Polygon::new(area.display_name(), points)
+                        .fill_color(color.gamma_multiply(0.1))
+                        .stroke((1.0, color))
+                        .style(egui_plot::LineStyle::dotted_loose()),
+                );
+                plot_ui.add(
+                    egui_plot::Text::new(
+                        "",
+                        plane.world_to_plot(region.center(), z_up),
+                        area.display_name(),
+                    )
+                    .color(color.gamma_multiply(0.75)),
+                );
+            }
+        }
+    }
+
+    /// Shows outlines for portals that have been added, removed, or moved in
+    /// the world being compared against.
+    fn show_comparison_in_plot(&self, plot_ui: &mut egui_plot::PlotUi<'_>, plane: Plane) {
+        let Some((_, diff)) = &self.comparison else {
+            return;
+        };
+        let z_up = self.prefs.positive_z_up;
+        let camera_dim = self.camera.dimension;
+        for dim in [camera_dim.other(), camera_dim] {
+            let opacity = if dim == camera_dim { 1.0 } else { 0.5 };
+            let show_outline =
+                |plot_ui: &mut egui_plot::PlotUi<'_>, region: BlockRegion, color: egui::Color32| {
+                    let region = WorldRegion::from(region).convert_dimension(dim, camera_dim);
+                    let a = plane.world_to_plot(region.min, z_up);
+                    let b = plane.world_to_plot(region.max, z_up);
+                    let points = vec![[a.x, a.y], [a.x, b.y], [b.x, b.y], [b.x, a.y]];
+                    plot_ui.add(
+                        egui_plot::Polygon::new("", points)
+                            .fill_color(color.gamma_multiply(0.1 * opacity))
+                            .stroke((2.0, color.gamma_multiply(opacity)))
+                            .style(egui_plot::LineStyle::dashed_loose()),
+                    );
+                    plane.world_to_plot(region.center(), z_up)
+                };
+
+            for portal in &diff.added[dim] {
+                show_outline(plot_ui, portal.region, COMPARISON_ADDED_COLOR);
+            }
+            for portal in &self.world.portals[dim] {
+                if diff.removed.contains(&portal.id) {
+                    show_outline(plot_ui, portal.region, COMPARISON_REMOVED_COLOR);
+                } else if let Some(&new_region) = diff.moved.get(&portal.id) {
+                    let old_center = show_outline(plot_ui, portal.region, COMPARISON_MOVED_COLOR);
+                    let new_center = show_outline(plot_ui, new_region, COMPARISON_MOVED_COLOR);
+                    plot_ui.add(
+                        egui_plot::Line::new(
+                            "",
+                            vec![[old_center.x, old_center.y], [new_center.x, new_center.y]],
+                        )
+                        .color(COMPARISON_MOVED_COLOR.gamma_multiply(opacity))
+                        .style(egui_plot::LineStyle::dotted_loose()),
+                    );
+                }
+            }
+        }
+    }
+
+    fn show_routes_in_plot(&self, plot_ui: &mut egui_plot::PlotUi<'_>, plane: Plane) {
+        let z_up = self.prefs.positive_z_up;
+        let camera_dim = self.camera.dimension;
+        for dim in [camera_dim.other(), camera_dim] {
+            let opacity = if dim == camera_dim { 1.0 } else { 0.5 };
+            for route in &self.world.routes[dim] {
+                let points = route
+                    .points
+                    .iter()
+                    .map(|p| plane.world_to_plot(p.convert_dimension(dim, camera_dim), z_up))
+                    .collect_vec();
+                let [r, g, b] = route.color;
+                let color = egui::Color32::from_rgb(r, g, b).gamma_multiply(opacity);
+                if let Some(&first) = points.first() {
+                    plot_ui.add(
+                        egui_plot::Text::new("", first, route.display_name())
+                            .color(color)
+                            .anchor(egui::Align2::LEFT_BOTTOM),
+                    );
+                }
+                plot_ui.add(
+                    egui_plot::Line::new(
+                        route.display_name(),
+                        egui_plot::PlotPoints::Owned(points),
+                    )
+                    .color(color)
+                    .width(2.0)
+                    .style(egui_plot::LineStyle::dashed_loose()),
+                );
+            }
+        }
+    }
+
     fn show_test_points_in_plot(&self, plot_ui: &mut egui_plot::PlotUi<'_>, plane: Plane) {
+        let z_up = self.prefs.positive_z_up;
         let dpos_dvalue_x = self.dpos_dvalue_x(plot_ui);
         for dim in [Overworld, Nether] {
             for &test_point in &self.world.test_points[dim] {
-                let plot_point =
-                    plane.world_to_plot(test_point.convert_dimension(dim, self.camera.dimension));
+                let plot_point = plane.world_to_plot(
+                    test_point.convert_dimension(dim, self.camera.dimension),
+                    z_up,
+                );
                 let destination_portals = self.world.portals.entity_destinations(dim, test_point);
                 let [r, g, b] = match destination_portals.first() {
                     Some(p) => p.color,
@@ -1027,79 +2631,344 @@ impl App {
                 plot_ui.add(
                     egui_plot::Points::new("", egui_plot::PlotPoints::Owned(vec![plot_point]))
                         .shape(egui_plot::MarkerShape::Diamond)
-                        .radius(dpos_dvalue_x.sqrt() / self.camera.dimension.scale() as f32 * 3.0)
+                        .radius(clamp_to_range(
+                            dpos_dvalue_x.sqrt() / self.camera.dimension.scale() as f32 * 3.0,
+                            TEST_POINT_RADIUS_RANGE,
+                        ))
                         .color(egui::Color32::from_rgb(r, g, b)),
                 );
             }
         }
-    }
 
-    fn process_portal_hovers(&mut self, dimension: Dimension, plane: Plane, hovered_pos: WorldPos) {
-        let WorldPos { x, y, z } = hovered_pos;
-        for portal in &self.world.portals[dimension] {
-            let WorldRegion { min, max } = WorldRegion::from(portal.region)
-                .convert_dimension(dimension, self.camera.dimension);
-            let x_range = min.x..=max.x;
-            let y_range = min.y..=max.y;
-            let z_range = min.z..=max.z;
-            let is_hovering_portal = match plane {
-                Plane::XY => x_range.contains(&x) && y_range.contains(&y),
-                Plane::XZ => x_range.contains(&x) && z_range.contains(&z),
-                Plane::ZY => z_range.contains(&z) && y_range.contains(&y),
-            };
-            if is_hovering_portal {
-                self.portals_hovered.in_plot_for_next_frame.push(portal.id);
-            }
+        if let Some((dim, pos)) = self.go_to_target {
+            let plot_point =
+                plane.world_to_plot(pos.convert_dimension(dim, self.camera.dimension), z_up);
+            plot_ui.add(
+                egui_plot::Points::new("", egui_plot::PlotPoints::Owned(vec![plot_point]))
+                    .shape(egui_plot::MarkerShape::Cross)
+                    .radius(TEST_POINT_RADIUS_RANGE.end() * 1.5)
+                    .color(egui::Color32::YELLOW),
+            );
         }
     }
 
-    fn calculate_portal_link_result(
-        &self,
-        portal: &Portal,
-        portal_dimension: Dimension,
-    ) -> PortalLinkResult {
-        let destination_dimension = portal_dimension.other();
-        let Some(entry_region) = portal.entity_collision_region(self.prefs.entity) else {
-            return PortalLinkResult::EntityWontFit;
+    /// Draws a marker at the player's live position, if any.
+    #[cfg(not(target_arch = "wasm32"))]
+    fn show_live_position_in_plot(&self, plot_ui: &mut egui_plot::PlotUi<'_>, plane: Plane) {
+        let Some((dim, pos)) = self.live_position.as_ref().and_then(|l| l.pos()) else {
+            return;
         };
-        let destination_region =
-            entry_region.convert_dimension(portal_dimension, destination_dimension);
-        let destinations = self.world.portals.portal_destinations(
-            destination_dimension,
-            destination_region.block_region_containing(),
+        let plot_point = plane.world_to_plot(
+            pos.convert_dimension(dim, self.camera.dimension),
+            self.prefs.positive_z_up,
         );
-        PortalLinkResult::Portals {
-            ids: destinations.existing_portals.iter().map(|p| p.id).collect(),
-            new_portal: destinations.new_portal,
+        plot_ui.add(
+            egui_plot::Points::new("", egui_plot::PlotPoints::Owned(vec![plot_point]))
+                .shape(egui_plot::MarkerShape::Circle)
+                .radius(TEST_POINT_RADIUS_RANGE.end() * 1.5)
+                .color(LIVE_POSITION_COLOR),
+        );
+        plot_ui.add(
+            egui_plot::Text::new("", plot_point, "You are here")
+                .anchor(egui::Align2::CENTER_BOTTOM)
+                .color(LIVE_POSITION_COLOR),
+        );
+    }
+
+    /// Moves a portal so that its minimum corner is at the coordinates in
+    /// `text`, keeping its size.
+    fn paste_portal_coordinates(&mut self, id: PortalId, text: &str) {
+        let Some(pos) = parse::parse_coordinates(text) else {
+            show_error_dialog((
+                "Error pasting coordinates",
+                format!("No coordinates found in {text:?}"),
+            ));
+            return;
+        };
+        if let Some((dimension, i)) = self.portal_index(id) {
+            self.world.portals[dimension][i].adjust_min(|min| *min = pos.floor(), true, dimension);
         }
     }
 
-    fn recalculate_portal_links(&mut self) {
-        self.cached_links.clear();
-
-        // Add outgoing connections
-        for portal_dimension in [Overworld, Nether] {
-            for portal in &self.world.portals[portal_dimension] {
-                self.cached_links.insert(
-                    portal.id,
-                    (
-                        self.calculate_portal_link_result(portal, portal_dimension),
-                        vec![],
-                    ),
-                );
+    /// Expands or collapses every portal in the portal list for `dimension`.
+    fn set_all_portals_expanded(&self, ctx: &egui::Context, dimension: Dimension, open: bool) {
+        for portal in &self.world.portals[dimension] {
+            let mut state = egui::collapsing_header::CollapsingState::load_with_default_open(
+                ctx,
+                portal_header_id(portal.id),
+                false,
+            );
+            state.set_open(open);
+            state.store(ctx);
+        }
+    }
+
+    /// Reorders the portals in `dimension`.
+    fn sort_portals(&mut self, dimension: Dimension, key: PortalSortKey) {
+        let camera_pos = self.camera.pos_in(dimension);
+        let distance_sq = |p: &Portal| {
+            let d = WorldRegion::from(p.region).center() - camera_pos;
+            d.x * d.x + d.z * d.z
+        };
+        // Problems first, then ambiguous links, then everything else.
+        let link_status_rank = |p: &Portal| match self.cached_links.get(&p.id) {
+            Some((PortalLinkResult::EntityWontFit(_), _)) => 0,
+            Some((
+                PortalLinkResult::Portals {
+                    new_portal: true, ..
+                },
+                _,
+            )) => 1,
+            Some((PortalLinkResult::Portals { ids, .. }, _)) if ids.len() > 1 => 2,
+            Some((PortalLinkResult::Portals { .. }, _)) => 3,
+            None => 4,
+        };
+        let portals = &mut self.world.portals[dimension];
+        match key {
+            PortalSortKey::Name => portals.sort_by_cached_key(|p| p.name.to_lowercase()),
+            PortalSortKey::X => portals.sort_by_key(|p| p.region.min.x),
+            PortalSortKey::Z => portals.sort_by_key(|p| p.region.min.z),
+            PortalSortKey::DistanceFromCamera => {
+                portals.sort_by(|a, b| distance_sq(a).total_cmp(&distance_sq(b)));
+            }
+            PortalSortKey::LinkStatus => portals.sort_by_key(link_status_rank),
+        }
+    }
+
+    /// Returns whether `portal` is shown in the plots, which is not the case
+    /// if it is hidden or if another portal is soloed.
+    fn is_shown_in_plot(&self, portal: &Portal) -> bool {
+        if self.hidden_portals.contains(&portal.id) {
+            return false;
+        }
+        let Some(solo) = self.solo_portal.filter(|&id| id != portal.id) else {
+            return true;
+        };
+        match self.cached_links.get(&solo) {
+            Some((outgoing, incoming)) => {
+                incoming.contains(&portal.id)
+                    || matches!(outgoing, PortalLinkResult::Portals { ids, .. } if ids.contains(&portal.id))
+            }
+            None => true, // soloed portal was deleted
+        }
+    }
+
+    /// Returns the portals in `dimension` that are under `pos` in the plot.
+    fn portals_at(&self, dimension: Dimension, plane: Plane, pos: WorldPos) -> Vec<PortalId> {
+        let WorldPos { x, y, z } = pos;
+        let mut ret = vec![];
+        for portal in self.world.portals[dimension].iter() {
+            if !self.is_shown_in_plot(portal) {
+                continue;
+            }
+            let WorldRegion { min, max } = WorldRegion::from(portal.region)
+                .convert_dimension(dimension, self.camera.dimension);
+            let x_range = min.x..=max.x;
+            let y_range = min.y..=max.y;
+            let z_range = min.z..=max.z;
+            let is_hovering_portal = match plane {
+                Plane::XY => x_range.contains(&x) && y_range.contains(&y),
+                Plane::XZ => x_range.contains(&x) && z_range.contains(&z),
+                Plane::ZY => z_range.contains(&z) && y_range.contains(&y),
+            };
+            if is_hovering_portal {
+                ret.push(portal.id);
+            }
+        }
+        ret
+    }
+
+    /// Updates the selection after clicking on `portals` in the plot.
+    fn click_portals_in_plot(&mut self, ui: &egui::Ui, portals: &[PortalId]) {
+        if ui.input(|input| input.modifiers.command || input.modifiers.shift) {
+            for &id in portals {
+                match self.selected_portals.iter().position(|&it| it == id) {
+                    Some(i) => {
+                        self.selected_portals.remove(i);
+                    }
+                    None => self.selected_portals.push(id),
+                }
+            }
+        } else {
+            self.selected_portals = portals.to_vec();
+        }
+    }
+
+    /// Returns the dimension and index of the portal with ID `id`.
+    fn portal_index(&self, id: PortalId) -> Option<(Dimension, usize)> {
+        self.portal_lookup.position(&self.world.portals, id)
+    }
+
+    fn show_plot_context_menu(&mut self, ui: &mut egui::Ui) {
+        let Some((dimension, i)) = self
+            .plot_context_menu_portal
+            .and_then(|id| self.portal_index(id))
+        else {
+            ui.weak("No portal here");
+            return;
+        };
+        ui.horizontal(|ui| {
+            ui.label("Name");
+            egui::TextEdit::singleline(&mut self.world.portals[dimension][i].name)
+                .hint_text("Portal name")
+                .show(ui);
+        });
+        ui.separator();
+        if ui.button("Duplicate").clicked() {
+            let mut new_portal = self.world.portals[dimension][i].clone();
+            new_portal.id = PortalId::new();
+            new_portal.file_id = None;
+            let mut offset = BlockPos::default();
+            offset[new_portal.width_axis()] = new_portal.width() + 1;
+            new_portal.translate(offset, dimension);
+            self.world.portals[dimension].insert(i + 1, new_portal);
+            ui.close();
+        }
+        if ui.button("Create Linked Pair").clicked() {
+            let new_portal = self.world.portals[dimension][i].new_linked_pair(dimension);
+            self.world.portals[dimension.other()].push(new_portal);
+            ui.close();
+        }
+        if ui.button("Copy Coordinates").clicked() {
+            let BlockRegion { min, max } = self.world.portals[dimension][i].region;
+            ui.ctx().copy_text(format!(
+                "{} {} {} {} {} {}",
+                min.x, min.y, min.z, max.x, max.y, max.z,
+            ));
+            ui.close();
+        }
+        ui.separator();
+        if ui.button("Delete").clicked() {
+            let id = self.world.portals[dimension].remove(i).id;
+            self.selected_portals.retain(|&it| it != id);
+            ui.close();
+        }
+    }
+
+    /// Applies `f` to the selected portals in each dimension.
+    fn arrange_selected_portals(&mut self, f: impl Fn(&mut [&mut Portal], Dimension)) {
+        for dim in [Overworld, Nether] {
+            let mut portals = self.world.portals[dim]
+                .iter_mut()
+                .filter(|p| self.selected_portals.contains(&p.id))
+                .collect_vec();
+            f(&mut portals, dim);
+        }
+    }
+
+    /// Applies `f` to each selected portal, along with the block at the center
+    /// of the camera in the portal's dimension.
+    fn transform_selected_portals(&mut self, f: impl Fn(&mut Portal, BlockPos)) {
+        for dim in [Overworld, Nether] {
+            let pivot = BlockPos::from(self.camera.pos_in(dim));
+            for portal in &mut self.world.portals[dim] {
+                if self.selected_portals.contains(&portal.id) {
+                    f(portal, pivot);
+                }
+            }
+        }
+    }
+
+    /// Returns the dimension and index of the source portal, along with the
+    /// target portal, if exactly two portals are selected and they are in
+    /// different dimensions.
+    fn selected_link_pair(&self) -> Option<(Dimension, usize, PortalId)> {
+        let &[source_id, target_id] = self.selected_portals.as_slice() else {
+            return None;
+        };
+        let (source_dimension, i) = self.portal_index(source_id)?;
+        let (target_dimension, _) = self.portal_index(target_id)?;
+        (source_dimension != target_dimension).then_some((source_dimension, i, target_id))
+    }
+
+    fn make_link(&mut self, (source_dimension, i, target_id): (Dimension, usize, PortalId)) {
+        let source = &self.world.portals[source_dimension][i];
+        let Some((_, target)) = self.world.portals.get(target_id) else {
+            return;
+        };
+        match arrange::link_offset(
+            &self.world.portals,
+            source,
+            source_dimension,
+            target,
+            self.prefs.entity,
+        ) {
+            Ok(offset) => {
+                self.world.portals[source_dimension][i].translate(offset, source_dimension);
             }
+            Err(e) => show_error_dialog(("Cannot make link", e)),
         }
+    }
 
-        // Add incoming connections
-        for (id, (outgoing, _)) in self.cached_links.clone() {
-            if let PortalLinkResult::Portals { ids, new_portal: _ } = outgoing {
-                for destination_id in ids {
-                    match self.cached_links.get_mut(&destination_id) {
-                        Some((_, incoming)) => incoming.push(id),
-                        None => log::error!("no destination portal with id {destination_id}"),
+    /// Returns whether `cached_links` is out of date because the world or
+    /// entity has changed since links were last calculated.
+    fn are_links_stale(&self) -> bool {
+        let (cached_world, cached_entity) = &self.cached_state;
+        (cached_world, cached_entity) != (&self.world, &self.prefs.entity)
+    }
+
+    /// Records which real entity presets take each link from the hovered portals
+    /// and from the portals that link to them, for annotating their arrows.
+    fn update_link_entities(&mut self) {
+        if self.links_stale {
+            return;
+        }
+        let hovered = self
+            .portals_hovered
+            .in_list
+            .iter()
+            .chain(&self.portals_hovered.in_plot);
+        let sources = hovered
+            .flat_map(|id| {
+                let incoming = self.cached_links.get(id).map(|(_, incoming)| incoming);
+                std::iter::once(id).chain(incoming.into_iter().flatten())
+            })
+            .copied()
+            .collect_vec();
+        for src_id in sources {
+            if self.cached_link_entities.contains_key(&src_id) {
+                continue;
+            }
+            let Some((dim, src)) = self.portal_lookup.get(&self.world.portals, src_id) else {
+                continue;
+            };
+            let mut entities = HashMap::<PortalId, Vec<&'static str>>::new();
+            for (name, entity) in real_entity_presets() {
+                let step_limit = self.prefs.link_search_step_limit;
+                let result = links::link_result(&self.world.portals, src, dim, entity, step_limit);
+                if let PortalLinkResult::Portals { ids, .. } = result {
+                    for id in ids {
+                        entities.entry(id).or_default().push(name);
                     }
                 }
             }
+            self.cached_link_entities.insert(src_id, entities);
+        }
+    }
+
+    fn recalculate_portal_links(&mut self) {
+        let old_links = std::mem::take(&mut self.cached_links);
+
+        let step_limit = self.prefs.link_search_step_limit;
+        self.cached_links = links::LinkReport::new(
+            &self.world,
+            self.prefs.entity,
+            step_limit,
+            self.prefs.new_portal_placement,
+        )
+        .to_link_map();
+
+        self.cached_link_entities.clear();
+        self.cached_shortcuts = travel::best_shortcuts(&self.world, &self.cached_links, Overworld);
+        self.cached_loops = (self.world.portals.iter())
+            .filter_map(|p| Some((p.id, links::find_loop(&self.cached_links, p.id)?)))
+            .collect();
+
+        // Flash portals whose link changed
+        for (id, (outgoing, _)) in &self.cached_links {
+            if old_links.get(id).is_some_and(|(old, _)| old != outgoing) {
+                self.animation_state.link_changed.insert(*id, 1.0);
+            }
         }
     }
 
@@ -1149,6 +3018,21 @@ impl App {
                         self.open();
                         ui.close();
                     }
+                    if ui.button("Merge…").clicked() {
+                        self.merge();
+                        ui.close();
+                    }
+                    if ui.button("Compare…").clicked() {
+                        self.compare();
+                        ui.close();
+                    }
+                    if let Some((_, diff)) = &self.comparison {
+                        ui.weak(diff.summary());
+                        if ui.button("Stop Comparing").clicked() {
+                            self.comparison = None;
+                            ui.close();
+                        }
+                    }
                     ui.separator();
                     if button_with_kbd(ui, "Save", &kbd_shortcuts::SAVE).clicked() {
                         self.save();
@@ -1158,11 +3042,24 @@ impl App {
                         self.save_as();
                         ui.close();
                     }
-                    #[cfg(not(target_arch = "wasm32"))]
-                    {
-                        ui.separator();
-                        ui.checkbox(&mut self.prefs.autosave, "Auto Save");
-                    }
+                    ui.separator();
+                    ui.checkbox(&mut self.prefs.autosave, "Auto Save")
+                        .on_hover_text(if IS_WEB {
+                            "Keep unsaved work in browser storage"
+                        } else {
+                            "Save changes to the current file automatically"
+                        });
+                    ui.checkbox(&mut self.prefs.save_portal_ids, "Save Portal IDs")
+                        .on_hover_text(
+                            "Save an ID with each portal, which is kept when \
+                             the file is loaded and saved again. \
+                             IDs are always saved if there are intended links.",
+                        );
+                    ui.checkbox(&mut self.prefs.undo_across_files, "Undo Across Files")
+                        .on_hover_text(
+                            "Keep undo history when opening a file, \
+                             so that opening a file can be undone",
+                        );
                     ui.separator();
                     if button_with_kbd(ui, "Import/Export…", &kbd_shortcuts::IMPORT_EXPORT)
                         .clicked()
@@ -1170,6 +3067,52 @@ impl App {
                         self.toggle_import_export();
                         ui.close();
                     }
+                    if ui.button("Export Link Graph…").clicked() {
+                        let dot = export::to_dot(&self.world, &self.cached_links);
+                        self.export(dot, "GraphViz", "dot");
+                        ui.close();
+                    }
+                    if ui.button("Export Link Table…").clicked() {
+                        let table = export::to_markdown_table(&self.world, &self.cached_links);
+                        self.export(table, "Markdown", "md");
+                        ui.close();
+                    }
+                    if ui
+                        .button("Export Link Report…")
+                        .on_hover_text(
+                            "Export every link for the current entity as JSON, \
+                             for use by other tools",
+                        )
+                        .clicked()
+                    {
+                        let report = links::LinkReport::new(
+                            &self.world,
+                            self.prefs.entity,
+                            self.prefs.link_search_step_limit,
+                            self.prefs.new_portal_placement,
+                        );
+                        match serde_json::to_string_pretty(&report) {
+                            Ok(json) => self.export(json, "JSON", "json"),
+                            Err(e) => show_error_dialog(("Export error", e)),
+                        }
+                        ui.close();
+                    }
+                    if ui.button("Copy Link Table").clicked() {
+                        let table = export::to_markdown_table(&self.world, &self.cached_links);
+                        ui.ctx().copy_text(table);
+                        ui.close();
+                    }
+                    if ui
+                        .button("Export Build Function…")
+                        .on_hover_text(
+                            "Export a .mcfunction file that builds and lights every portal",
+                        )
+                        .clicked()
+                    {
+                        let function = export::to_mcfunction(&self.world);
+                        self.export(function, "Minecraft function", "mcfunction");
+                        ui.close();
+                    }
 
                     // no File->Quit on web pages
                     if !IS_WEB {
@@ -1197,6 +3140,42 @@ impl App {
                             self.redo();
                         }
                     });
+
+                    ui.separator();
+
+                    if button_with_kbd(ui, "Add Portal at Camera", &kbd_shortcuts::ADD_PORTAL)
+                        .on_hover_text(format!("Add a portal in the {}", self.camera.dimension))
+                        .clicked()
+                    {
+                        self.add_portal(self.camera.dimension);
+                        ui.close();
+                    }
+                    ui.menu_button("Add Portal From Template", |ui| {
+                        for template in self.prefs.portal_templates.clone() {
+                            if ui.button(&template.name).clicked() {
+                                self.add_portal_from_template(self.camera.dimension, &template);
+                                ui.close();
+                            }
+                        }
+                        if !self.prefs.portal_templates.is_empty() {
+                            ui.separator();
+                        }
+                        if ui.button("Edit Templates…").clicked() {
+                            self.templates_window_open = true;
+                            ui.close();
+                        }
+                    });
+                    if ui
+                        .button("Paste Coordinates…")
+                        .on_hover_text(
+                            "Add test points or portals from coordinates \
+                             copied out of the game",
+                        )
+                        .clicked()
+                    {
+                        self.paste_coordinates_modal = Some(String::new());
+                        ui.close();
+                    }
                 });
 
                 menu_no_autoclose(ui, "View", |ui| {
@@ -1210,35 +3189,297 @@ impl App {
                     let button = egui::Button::new("Reset camera")
                         .shortcut_text(ui.ctx().format_shortcut(&kbd_shortcuts::RESET_CAMERA));
                     if ui.add(button).clicked() {
-                        self.camera.reset();
+                        self.reset_camera();
                         ui.close();
                     }
 
                     ui.separator();
 
-                    ui.checkbox(&mut self.prefs.show_zy_plot, "Show ZY Plot");
+                    let mut show_zy_plot = self.show_zy_plot();
+                    if ui.checkbox(&mut show_zy_plot, "Show ZY Plot").changed() {
+                        self.set_show_zy_plot(show_zy_plot);
+                    }
+                    ui.checkbox(&mut self.prefs.show_minimap, "Show Minimap");
+                    ui.checkbox(&mut self.prefs.show_crosshair, "Show Crosshair")
+                        .on_hover_text("Mark the camera position, where new portals are added");
+                    ui.menu_button("Also Outline Entities", |ui| {
+                        for ((name, _), color) in
+                            std::iter::zip(ENTITY_PRESETS, ENTITY_OUTLINE_COLORS)
+                        {
+                            let outlines = &mut self.prefs.outline_entities;
+                            let mut enabled = outlines.iter().any(|n| n == name);
+                            let text = egui::RichText::new(name).color(color);
+                            if ui.checkbox(&mut enabled, text).changed() {
+                                outlines.retain(|n| n != name);
+                                if enabled {
+                                    outlines.push(name.to_owned());
+                                }
+                            }
+                        }
+                    })
+                    .response
+                    .on_hover_text(
+                        "Outline where other entities collide with the hovered portal, \
+                         to compare them with the current entity",
+                    );
+                    ui.checkbox(&mut self.prefs.positive_z_up, "Show +Z at Top of XZ Plot")
+                        .on_hover_text(
+                            "Show south (+Z) at the top of the XZ plot instead of north, \
+                             so that coordinates increase upwards",
+                        );
+                    ui.checkbox(&mut self.prefs.keep_blocks_square, "Keep Blocks Square")
+                        .on_hover_text(
+                            "Don't stretch the XY and ZY plots \
+                             when switching dimensions",
+                        );
+                    ui.checkbox(
+                        &mut self.prefs.independent_cameras,
+                        "Separate Camera Per Dimension",
+                    )
+                    .on_hover_text(
+                        "When switching dimensions, return to where the camera \
+                         was in that dimension instead of the corresponding position",
+                    );
                     ui.checkbox(
                         &mut self.prefs.show_both_portal_lists,
                         "Show Both Portal Lists",
                     );
+                    ui.checkbox(
+                        &mut self.prefs.show_converted_coordinates,
+                        "Show Converted Coordinates",
+                    )
+                    .on_hover_text(
+                        "Show where each portal's blocks are \
+                         in the other dimension's coordinates",
+                    );
+                    ui.checkbox(
+                        &mut self.prefs.preview_links_at_cursor,
+                        "Preview Links At Cursor",
+                    )
+                    .on_hover_text(
+                        "When hovering empty space in the plot, show where \
+                         an entity arriving there would link to",
+                    );
+                    ui.checkbox(
+                        &mut self.prefs.preview_links_at_camera,
+                        "Preview Links At Camera",
+                    )
+                    .on_hover_text(
+                        "Show where an entity at the camera position \
+                         would link to, updating as the camera moves",
+                    );
 
                     ui.separator();
 
-                    ui.checkbox(&mut self.prefs.show_all_labels, "Show Portal Labels");
-                    ui.checkbox(&mut self.prefs.show_all_arrows, "Show Link Arrows");
+                    // Settings saved with the world can only be changed by
+                    // removing them from the world.
+                    let display = self.world.display;
+                    let mut show_all_labels = self.show_all_labels();
+                    ui.add_enabled(
+                        display.show_all_labels.is_none(),
+                        egui::Checkbox::new(&mut show_all_labels, "Show Portal Labels"),
+                    )
+                    .on_disabled_hover_text(SAVED_DISPLAY_SETTING_HOVER_TEXT);
+                    let mut show_all_arrows = self.show_all_arrows();
+                    ui.add_enabled(
+                        display.show_all_arrows.is_none(),
+                        egui::Checkbox::new(&mut show_all_arrows, "Show Link Arrows"),
+                    )
+                    .on_disabled_hover_text(SAVED_DISPLAY_SETTING_HOVER_TEXT);
+                    if display.show_all_labels.is_none() {
+                        self.prefs.show_all_labels = show_all_labels;
+                    }
+                    if display.show_all_arrows.is_none() {
+                        self.prefs.show_all_arrows = show_all_arrows;
+                    }
                     ui.horizontal(|ui| {
+                        ui.strong("Hovered portal shows");
+                        for (direction, text, hover_text) in [
+                            (
+                                LinkDirection::Both,
+                                "Both",
+                                "Show all links to and from the hovered portal",
+                            ),
+                            (
+                                LinkDirection::Outgoing,
+                                "Outgoing",
+                                "Show only where the hovered portal leads",
+                            ),
+                            (
+                                LinkDirection::Incoming,
+                                "Incoming",
+                                "Show only which portals lead to the hovered portal",
+                            ),
+                        ] {
+                            ui.selectable_value(
+                                &mut self.prefs.focused_link_direction,
+                                direction,
+                                text,
+                            )
+                            .on_hover_text(hover_text);
+                        }
+                    });
+                    let mut arrow_coloring = self.arrow_coloring();
+                    let r = ui.horizontal(|ui| {
+                        if display.arrow_coloring.is_some() {
+                            ui.disable();
+                        }
                         ui.strong("Color arrows by");
+                        let arrow_coloring = &mut arrow_coloring;
+                        ui.selectable_value(arrow_coloring, ArrowColoring::BySource, "Source");
                         ui.selectable_value(
-                            &mut self.prefs.arrow_coloring,
-                            ArrowColoring::BySource,
-                            "Source",
-                        );
-                        ui.selectable_value(
-                            &mut self.prefs.arrow_coloring,
+                            arrow_coloring,
                             ArrowColoring::ByDestination,
                             "Destination",
                         );
+                        ui.selectable_value(
+                            arrow_coloring,
+                            ArrowColoring::ByDimension,
+                            "Dimension",
+                        )
+                        .on_hover_text(
+                            "Blue for links into the Overworld, orange for links into the Nether",
+                        );
+                        ui.selectable_value(arrow_coloring, ArrowColoring::ByQuality, "Quality")
+                            .on_hover_text(
+                                "Green for links to exactly one portal, \
+                             yellow for ambiguous links, \
+                             red for portals that may generate a new portal",
+                            );
+                    });
+                    if display.arrow_coloring.is_some() {
+                        r.response.on_hover_text(SAVED_DISPLAY_SETTING_HOVER_TEXT);
+                    } else {
+                        self.prefs.arrow_coloring = arrow_coloring;
+                    }
+                });
+
+                menu_no_autoclose(ui, "Transform", |ui| {
+                    ui.weak(format!(
+                        "{} selected, around camera center",
+                        self.selected_portals.len(),
+                    ));
+                    ui.add_enabled_ui(!self.selected_portals.is_empty(), |ui| {
+                        if ui.button("Rotate Clockwise").clicked() {
+                            self.transform_selected_portals(|p, pivot| p.rotate(pivot, true));
+                        }
+                        if ui.button("Rotate Counterclockwise").clicked() {
+                            self.transform_selected_portals(|p, pivot| p.rotate(pivot, false));
+                        }
+                        ui.separator();
+                        if ui.button("Mirror Along X").clicked() {
+                            self.transform_selected_portals(|p, pivot| p.mirror(Axis::X, pivot));
+                        }
+                        if ui.button("Mirror Along Z").clicked() {
+                            self.transform_selected_portals(|p, pivot| p.mirror(Axis::Z, pivot));
+                        }
+                        ui.separator();
+                        egui::Grid::new("align_buttons").show(ui, |ui| {
+                            for axis in Axis::ALL {
+                                ui.label(format!("Align {axis}"));
+                                for (anchor, text) in [
+                                    (arrange::AlignAnchor::Min, "Min"),
+                                    (arrange::AlignAnchor::Center, "Center"),
+                                    (arrange::AlignAnchor::Max, "Max"),
+                                ] {
+                                    if ui.button(text).clicked() {
+                                        self.arrange_selected_portals(|portals, dim| {
+                                            arrange::align(portals, axis, anchor, dim);
+                                        });
+                                    }
+                                }
+                                ui.end_row();
+                            }
+                        });
+                        ui.horizontal(|ui| {
+                            ui.label("Distribute");
+                            for axis in Axis::ALL {
+                                if ui.button(axis.to_string()).clicked() {
+                                    self.arrange_selected_portals(|portals, dim| {
+                                        arrange::distribute(portals, axis, dim);
+                                    });
+                                }
+                            }
+                        });
+                        ui.separator();
+                        let link_pair = self.selected_link_pair();
+                        let r = ui
+                            .add_enabled(link_pair.is_some(), egui::Button::new("Make Link"))
+                            .on_hover_text(
+                                "Move the first selected portal so that it links to the second",
+                            );
+                        if r.clicked()
+                            && let Some(link_pair) = link_pair
+                        {
+                            self.make_link(link_pair);
+                        }
+                        ui.separator();
+                        if ui.button("Clear Selection").clicked() {
+                            self.selected_portals.clear();
+                        }
                     });
+                    ui.separator();
+                    if ui.button("Offset All…").clicked() {
+                        self.offset_all_modal = Some((self.camera.dimension, BlockPos::default()));
+                        ui.close();
+                    }
+                });
+
+                menu_no_autoclose(ui, "Tools", |ui| {
+                    if ui.button("Annotations…").clicked() {
+                        self.annotations_window_open ^= true;
+                        ui.close();
+                    }
+                    if ui.button("Explain Link…").clicked() {
+                        self.explain_link_window_open ^= true;
+                        ui.close();
+                    }
+                    if ui.button("Statistics…").clicked() {
+                        self.stats_window_open ^= true;
+                        ui.close();
+                    }
+                    if ui.button("Go to Coordinates…").clicked() {
+                        self.go_to_window_open ^= true;
+                        ui.close();
+                    }
+                    if ui.button("Batch Evaluate…").clicked() {
+                        self.batch_evaluate_modal = Some((self.camera.dimension, String::new()));
+                        ui.close();
+                    }
+                    if ui.button("Build Checklist…").clicked() {
+                        self.build_checklist_modal_text =
+                            Some(checklist::build_checklist(&self.world, &self.cached_links));
+                        ui.close();
+                    }
+                    #[cfg(not(target_arch = "wasm32"))]
+                    {
+                        ui.separator();
+                        if ui
+                            .button("Live Position From File…")
+                            .on_hover_text(include_str!("text/live_position.txt").trim())
+                            .clicked()
+                        {
+                            self.spawn_async_task(async move || {
+                                Ok(match rfd::AsyncFileDialog::new().pick_file().await {
+                                    Some(file_handle) => AppAsyncTaskOk::SetLivePositionFile {
+                                        path: file_handle.path().to_path_buf(),
+                                    },
+                                    None => AppAsyncTaskOk::None,
+                                })
+                            });
+                            ui.close();
+                        }
+                        if let Some(live_position) = &self.live_position {
+                            if ui
+                                .button("Stop Live Position")
+                                .on_hover_text(live_position.path().display().to_string())
+                                .clicked()
+                            {
+                                self.live_position = None;
+                                ui.close();
+                            }
+                        }
+                    }
                 });
 
                 menu_no_autoclose(ui, "Settings", |ui| {
@@ -1252,132 +3493,1138 @@ impl App {
                         "Lock Portal Size When Editing",
                     )
                     .on_hover_text(include_str!("text/lock_portal_size.txt").trim());
+                    ui.horizontal(|ui| {
+                        ui.label("Snap Increment");
+                        ui.add(
+                            egui::DragValue::new(&mut self.prefs.snap_increment).range(1..=1024),
+                        );
+                    })
+                    .response
+                    .on_hover_text(
+                        "Hold Alt while dragging coordinates or portals \
+                         to snap to multiples of this many blocks",
+                    );
+                    ui.horizontal(|ui| {
+                        ui.label("Link Search Step Limit");
+                        ui.add(
+                            egui::DragValue::new(&mut self.prefs.link_search_step_limit)
+                                .range(0..=1_000_000)
+                                .speed(16),
+                        );
+                    })
+                    .response
+                    .on_hover_text(include_str!("text/link_search_step_limit.txt").trim());
+                    ui.horizontal(|ui| {
+                        ui.label("Travel Speed");
+                        egui::ComboBox::from_id_salt("travel_speed")
+                            .selected_text(self.prefs.travel_speed.name())
+                            .show_ui(ui, |ui| {
+                                for speed in TravelSpeed::ALL {
+                                    ui.selectable_value(
+                                        &mut self.prefs.travel_speed,
+                                        speed,
+                                        speed.name(),
+                                    );
+                                }
+                            });
+                    })
+                    .response
+                    .on_hover_text("Mode of travel used to estimate travel times");
+                    ui.horizontal(|ui| {
+                        ui.checkbox(
+                            &mut self.prefs.warn_far_portals,
+                            "Warn About Portals Beyond",
+                        );
+                        ui.add_enabled(
+                            self.prefs.warn_far_portals,
+                            egui::DragValue::new(&mut self.prefs.far_portal_distance)
+                                .range(0..=WORLD_BORDER_DISTANCE)
+                                .speed(1000),
+                        );
+                    })
+                    .response
+                    .on_hover_text(include_str!("text/far_portals.txt").trim());
                     ui.separator();
-                    egui::global_theme_preference_buttons(ui);
+                    egui::Grid::new("scroll_sensitivity").show(ui, |ui| {
+                        ui.label("Zoom Sensitivity");
+                        ui.add(
+                            egui::Slider::new(
+                                &mut self.prefs.zoom_sensitivity,
+                                SCROLL_SENSITIVITY_RANGE,
+                            )
+                            .logarithmic(true),
+                        )
+                        .on_hover_text("Zoom speed for Ctrl+scroll and pinching");
+                        ui.end_row();
+                        ui.label("Scroll Sensitivity");
+                        ui.add(
+                            egui::Slider::new(
+                                &mut self.prefs.scroll_sensitivity,
+                                SCROLL_SENSITIVITY_RANGE,
+                            )
+                            .logarithmic(true),
+                        )
+                        .on_hover_text("Speed for panning the plot and scrolling lists");
+                        ui.end_row();
+                    });
                     ui.separator();
-                    if ui.button("Reset all settings").clicked() {
-                        self.prefs = Preferences::default();
-                    };
+                    egui::Grid::new("new_portal_axis").show(ui, |ui| {
+                        for dim in [Overworld, Nether] {
+                            ui.label(format!("New {dim} Portals Facing"));
+                            let axis = &mut self.prefs.new_portal_axis[dim];
+                            ui.selectable_value(axis, PortalAxis::X, "X");
+                            ui.selectable_value(axis, PortalAxis::Z, "Z");
+                            ui.end_row();
+                        }
+                    });
+                    ui.horizontal(|ui| {
+                        let [width, height] = &mut self.prefs.new_portal_size;
+                        ui.label("New Portal Size");
+                        // 21×21 is the largest portal that can be lit.
+                        ui.add(egui::DragValue::new(width).range(Portal::MIN_WIDTH..=21));
+                        ui.label("×");
+                        ui.add(egui::DragValue::new(height).range(Portal::MIN_HEIGHT..=21));
+                    })
+                    .response
+                    .on_hover_text("Width and height of newly added portals");
+                    ui.checkbox(
+                        &mut self.prefs.remember_portal_axis,
+                        "Remember Last Used Facing",
+                    )
+                    .on_hover_text(
+                        "When a portal's facing is changed, \
+                         use it for new portals in the same dimension",
+                    );
+                    ui.separator();
+                    ui.strong("Generated Portals Assume");
+                    ui.selectable_value(
+                        &mut self.prefs.new_portal_placement,
+                        NewPortalPlacement::ExistingAir,
+                        "Existing Air Space",
+                    )
+                    .on_hover_text("Predict new portals exactly at the arrival position");
+                    ui.selectable_value(
+                        &mut self.prefs.new_portal_placement,
+                        NewPortalPlacement::Platform,
+                        "Obsidian Platform",
+                    )
+                    .on_hover_text(
+                        "Predict new portals on an obsidian platform, \
+                         which is built when there is no space nearby",
+                    );
+                    ui.separator();
+                    egui::global_theme_preference_buttons(ui);
+                    ui.separator();
+                    if ui
+                        .button("Save Display Settings in File")
+                        .on_hover_text(
+                            "Apply the current theme, labels, and arrow settings \
+                             whenever this file is opened",
+                        )
+                        .clicked()
+                    {
+                        self.world.display = DisplayOverrides {
+                            theme: Some(
+                                ui.ctx().options(|options| options.theme_preference.into()),
+                            ),
+                            show_all_labels: Some(self.show_all_labels()),
+                            show_all_arrows: Some(self.show_all_arrows()),
+                            arrow_coloring: Some(self.arrow_coloring()),
+                        };
+                    }
+                    ui.add_enabled_ui(!self.world.display.is_empty(), |ui| {
+                        if ui.button("Remove Display Settings From File").clicked() {
+                            self.world.display = DisplayOverrides::default();
+                        }
+                    });
+                    ui.separator();
+                    if ui.button("Reset all settings").clicked() {
+                        self.prefs = Preferences::default();
+                    };
+                });
+                menu_no_autoclose(ui, "Help", |ui| {
+                    if ui.button("Load Example World").clicked() {
+                        self.load_example();
+                        ui.close();
+                    }
+                });
+            };
+
+            if collapse_menu {
+                ui.menu_button("Menu", menu_contents);
+            } else {
+                menu_contents(ui);
+            }
+
+            ui.separator();
+
+            if self.restored_unsaved_work {
+                ui.colored_label(ui.visuals().warn_fg_color, "Restored unsaved work");
+                if ui.small_button("Dismiss").clicked() {
+                    self.restored_unsaved_work = false;
+                }
+                ui.separator();
+            }
+
+            let mut camera_controls_contents = |ui: &mut egui::Ui| {
+                ui.horizontal(|ui| {
+                    let mut new_camera_dimension = self.camera.dimension;
+                    for dim in [Overworld, Nether] {
+                        ui.selectable_value(&mut new_camera_dimension, dim, dim.to_string());
+                    }
+                    self.set_camera_dimension(new_camera_dimension);
+                });
+
+                if !collapse_camera_controls {
+                    ui.separator();
+                }
+
+                ui.horizontal(|ui| {
+                    if img_button(ui, egui::include_image!("img/home.svg"))
+                        .on_hover_text("Reset camera")
+                        .clicked()
+                    {
+                        self.reset_camera();
+                    }
+
+                    show_world_pos_edit(ui, &mut self.camera.pos, self.camera.dimension, Some(0));
+                });
+            };
+
+            if collapse_camera_controls {
+                menu_no_autoclose(ui, "Position", camera_controls_contents);
+            } else {
+                camera_controls_contents(ui);
+            }
+
+            ui.separator();
+
+            menu_no_autoclose(ui, "Entity size", |ui| self.show_entity_config(ui));
+
+            ui_unless_overflow(ui, |ui| ui.small(format!("{:#}", self.prefs.entity)));
+        });
+    }
+
+    fn show_import_export_modal(&mut self, ctx: &egui::Context) {
+        if let Some(mut text) = self.import_export_modal_text.take() {
+            let r = egui::Modal::new(egui::Id::new("import_export")).show(ctx, |ui| {
+                let r = egui::ScrollArea::vertical()
+                    .max_width(ui.ctx().screen_rect().width() / 2.0)
+                    .max_height(ui.ctx().screen_rect().height() / 4.0)
+                    .auto_shrink(false)
+                    .show(ui, |ui| {
+                        ui.with_layout(
+                            egui::Layout::top_down(egui::Align::LEFT)
+                                .with_cross_justify(true)
+                                .with_main_justify(true),
+                            |ui| {
+                                egui::TextEdit::multiline(&mut text)
+                                    .clip_text(false)
+                                    .show(ui)
+                                    .response
+                            },
+                        )
+                        .inner
+                    })
+                    .inner;
+                if r.changed() {
+                    self.cached_import_export_modal_text_deserialized = None;
+                }
+
+                let deserialized = self
+                    .cached_import_export_modal_text_deserialized
+                    .take()
+                    .unwrap_or_else(|| World::from_json_with_duplicate_ids(&text));
+
+                match &deserialized {
+                    Ok((_, duplicate_ids)) if !duplicate_ids.is_empty() => ui.colored_label(
+                        ui.visuals().warn_fg_color,
+                        duplicate_ids_warning(duplicate_ids),
+                    ),
+                    Ok(_) => ui.label(""),
+                    Err(e) => ui.colored_label(ui.visuals().error_fg_color, e.to_string()),
+                };
+
+                ui.horizontal(|ui| {
+                    ui.label("Import");
+                    ui.checkbox(&mut self.import_filter.portals, "Portals");
+                    ui.checkbox(&mut self.import_filter.test_points, "Test points");
+                    ui.checkbox(&mut self.import_filter.annotations, "Annotations");
+                });
+                ui.horizontal(|ui| {
+                    ui.label("From");
+                    ui.selectable_value(&mut self.import_filter.dimension, None, "Both dimensions");
+                    for dim in [Overworld, Nether] {
+                        ui.selectable_value(
+                            &mut self.import_filter.dimension,
+                            Some(dim),
+                            dim.to_string(),
+                        );
+                    }
+                });
+                ui.horizontal(|ui| {
+                    ui.selectable_value(&mut self.import_merge, false, "Replace current world")
+                        .on_hover_text("Replace only the parts of the current world chosen above");
+                    ui.selectable_value(&mut self.import_merge, true, "Merge into current world");
+                });
+
+                ui.horizontal(|ui| {
+                    if ui.button("Cancel").clicked() {
+                        ui.close();
+                    }
+
+                    if ui
+                        .add_enabled(deserialized.is_ok(), egui::Button::new("Import"))
+                        .clicked()
+                        && let Ok((world, _)) = &deserialized
+                    {
+                        let mut world = world.clone();
+                        if self.import_merge {
+                            world.filter(self.import_filter);
+                            self.merge_modal =
+                                Some((world, self.camera.dimension, BlockPos::default()));
+                            ui.close();
+                        } else if !self.import_filter.keeps_everything() {
+                            // Only replace the parts that were chosen.
+                            self.world.replace(world, self.import_filter);
+                            ui.close();
+                        } else if self.is_ok_to_discard_state() {
+                            self.load(world);
+                            ui.close();
+                        }
+                    }
+                });
+
+                self.cached_import_export_modal_text_deserialized = Some(deserialized);
+            });
+
+            if r.should_close() {
+                self.cached_import_export_modal_text_deserialized = None;
+            } else {
+                self.import_export_modal_text = Some(text);
+            }
+        }
+    }
+
+    fn show_annotations_window(&mut self, ctx: &egui::Context) {
+        let mut open = self.annotations_window_open;
+        egui::Window::new("Annotations")
+            .open(&mut open)
+            .default_width(400.0)
+            .show(ctx, |ui| {
+                egui::ScrollArea::vertical().show(ui, |ui| {
+                    for dim in [Overworld, Nether] {
+                        ui.heading(dim.to_string());
+                        self.show_route_list(ui, dim);
+                        self.show_area_list(ui, dim);
+                        ui.separator();
+                    }
+                });
+            });
+        self.annotations_window_open = open;
+        if !open {
+            self.drawing_route = None;
+        }
+    }
+
+    fn show_templates_window(&mut self, ctx: &egui::Context) {
+        let mut open = self.templates_window_open;
+        egui::Window::new("Portal Templates")
+            .open(&mut open)
+            .show(ctx, |ui| {
+                let mut remove = None;
+                let mut add = None;
+                egui::Grid::new("portal_templates")
+                    .striped(true)
+                    .show(ui, |ui| {
+                        ui.label("");
+                        ui.strong("Template");
+                        ui.strong("Portal name");
+                        ui.strong("Width");
+                        ui.strong("Height");
+                        ui.strong("Facing");
+                        ui.end_row();
+                        for (i, template) in self.prefs.portal_templates.iter_mut().enumerate() {
+                            ui.color_edit_button_srgb(&mut template.color);
+                            egui::TextEdit::singleline(&mut template.name)
+                                .desired_width(120.0)
+                                .show(ui);
+                            egui::TextEdit::singleline(&mut template.portal_name)
+                                .hint_text("Unnamed")
+                                .desired_width(100.0)
+                                .show(ui)
+                                .response
+                                .on_hover_text("{n} is replaced by the number of the new portal");
+                            ui.add(
+                                egui::DragValue::new(&mut template.width)
+                                    .range(Portal::MIN_WIDTH..=i64::MAX),
+                            );
+                            ui.add(
+                                egui::DragValue::new(&mut template.height)
+                                    .range(Portal::MIN_HEIGHT..=i64::MAX),
+                            );
+                            ui.horizontal(|ui| {
+                                ui.selectable_value(&mut template.axis, PortalAxis::X, "X");
+                                ui.selectable_value(&mut template.axis, PortalAxis::Z, "Z");
+                            });
+                            ui.horizontal(|ui| {
+                                if ui
+                                    .small_button("Add")
+                                    .on_hover_text(format!(
+                                        "Add a portal in the {}",
+                                        self.camera.dimension,
+                                    ))
+                                    .clicked()
+                                {
+                                    add = Some(i);
+                                }
+                                if img_button(ui, egui::include_image!("img/delete.svg"))
+                                    .on_hover_text("Delete template")
+                                    .clicked()
+                                {
+                                    remove = Some(i);
+                                }
+                            });
+                            ui.end_row();
+                        }
+                    });
+                if ui.button("New Template").clicked() {
+                    self.prefs.portal_templates.push(PortalTemplate {
+                        name: "New template".to_owned(),
+                        ..PortalTemplate::defaults()[0].clone()
+                    });
+                }
+                if let Some(i) = add {
+                    let template = self.prefs.portal_templates[i].clone();
+                    self.add_portal_from_template(self.camera.dimension, &template);
+                }
+                if let Some(i) = remove {
+                    self.prefs.portal_templates.remove(i);
+                }
+            });
+        self.templates_window_open = open;
+    }
+
+    fn show_stats_window(&mut self, ctx: &egui::Context) {
+        let mut open = self.stats_window_open;
+        egui::Window::new("Statistics")
+            .open(&mut open)
+            .show(ctx, |ui| {
+                let stats = links::NetworkStats::new(&self.world, &self.cached_links);
+                if self.links_stale {
+                    ui.horizontal(|ui| {
+                        ui.spinner();
+                        ui.weak("Recalculating ...");
+                    });
+                }
+                egui::Grid::new("stats").striped(true).show(ui, |ui| {
+                    let mut row = |label: &str, value: String| {
+                        ui.label(label);
+                        ui.label(value);
+                        ui.end_row();
+                    };
+                    for dim in [Overworld, Nether] {
+                        row(&format!("{dim} portals"), stats.portals[dim].to_string());
+                    }
+                    for dim in [Overworld, Nether] {
+                        row(
+                            &format!("{dim} test points"),
+                            stats.test_points[dim].to_string(),
+                        );
+                    }
+                    row("Links", stats.links.to_string());
+                    row("Ambiguous links", stats.ambiguous.to_string());
+                    row("Generate new portals", stats.new_portals.to_string());
+                    row("No portals in range", stats.isolated.to_string());
+                    row("Entity won't fit", stats.entity_wont_fit.to_string());
+                    row("No incoming links", stats.orphans.to_string());
+                    row(
+                        "Unmet intended links",
+                        stats.unmet_intended_links.to_string(),
+                    );
+                });
+            });
+        self.stats_window_open = open;
+    }
+
+    fn show_go_to_window(&mut self, ctx: &egui::Context) {
+        let mut open = self.go_to_window_open;
+        egui::Window::new("Go to Coordinates")
+            .open(&mut open)
+            .show(ctx, |ui| {
+                let (dimension, pos) = &mut self.go_to_pos;
+                ui.horizontal(|ui| {
+                    for dim in [Overworld, Nether] {
+                        ui.selectable_value(dimension, dim, dim.to_string());
+                    }
+                });
+                pos.y = dimension.clamp_entity_y(pos.y);
+                show_world_pos_edit(ui, pos, *dimension, None);
+                let (dimension, pos) = self.go_to_pos;
+
+                ui.horizontal(|ui| {
+                    if ui.button("Go").clicked() {
+                        self.go_to_target = Some((dimension, pos));
+                        self.set_camera_dimension(dimension);
+                        self.camera.pos = pos;
+                    }
+                    if ui
+                        .button("Copy /tp")
+                        .on_hover_text("Copy a command to teleport to these coordinates")
+                        .clicked()
+                    {
+                        let dimension_id = match dimension {
+                            Overworld => "minecraft:overworld",
+                            Nether => "minecraft:the_nether",
+                        };
+                        let WorldPos { x, y, z } = pos;
+                        ui.ctx().copy_text(format!(
+                            "/execute in {dimension_id} run tp @s {x} {y} {z}",
+                        ));
+                    }
+                });
+
+                let Some((target_dimension, target_pos)) = self.go_to_target else {
+                    return;
+                };
+                ui.separator();
+                ui.weak(format!(
+                    "Portals within {GO_TO_HIGHLIGHT_DISTANCE} blocks of \
+                     {target_pos} in the {target_dimension}:",
+                ));
+                let nearby = self.world.portals[target_dimension]
+                    .iter()
+                    .filter(|p| self.is_near_go_to_target(p, target_dimension))
+                    .map(|p| p.display_name())
+                    .collect_vec();
+                if nearby.is_empty() {
+                    ui.label("None");
+                }
+                for name in nearby {
+                    ui.label(name);
+                }
+            });
+        self.go_to_window_open = open;
+        if !open {
+            self.go_to_target = None;
+        }
+    }
+
+    /// Returns whether `portal` is close enough to the "Go to Coordinates"
+    /// target to be highlighted.
+    fn is_near_go_to_target(&self, portal: &Portal, dimension: Dimension) -> bool {
+        self.go_to_target.is_some_and(|(target_dimension, pos)| {
+            target_dimension == dimension
+                && portal
+                    .region
+                    .min_euclidean_distance_sq_to_point(pos.floor())
+                    <= GO_TO_HIGHLIGHT_DISTANCE.pow(2)
+        })
+    }
+
+    fn show_explain_link_window(&mut self, ctx: &egui::Context) {
+        let mut open = self.explain_link_window_open;
+        egui::Window::new("Explain Link")
+            .open(&mut open)
+            .show(ctx, |ui| {
+                let Some((dimension, i)) = self
+                    .selected_portals
+                    .last()
+                    .and_then(|&id| self.portal_index(id))
+                else {
+                    ui.weak("Select a portal to explain where it links");
+                    return;
+                };
+                let portal = &self.world.portals[dimension][i];
+                let destination_dimension = dimension.other();
+                let entity = self.prefs.entity;
+                if let Err(reason) = portal.try_entity_collision_region(entity) {
+                    ui.colored_label(ui.visuals().error_fg_color, reason.to_string());
+                    return;
+                }
+                let Some(explanation) =
+                    links::explain_link(&self.world.portals, portal, dimension, entity)
+                else {
+                    ui.colored_label(ui.visuals().error_fg_color, "Entity won't fit");
+                    return;
+                };
+
+                egui::Grid::new("explain_link")
+                    .striped(true)
+                    .show(ui, |ui| {
+                        ui.strong("Portal");
+                        ui.label(format!("{} ({dimension})", portal.display_name()));
+                        ui.end_row();
+
+                        ui.strong("Entity");
+                        ui.label(format!("{entity:#}"));
+                        ui.end_row();
+
+                        let WorldRegion { min, max } = explanation.collision_region;
+                        ui.strong("Collision region");
+                        ui.label(format!("{min:.3} to {max:.3}"));
+                        ui.end_row();
+
+                        let BlockRegion { min, max } = explanation.destination_region;
+                        ui.strong(format!("Destination region ({destination_dimension})"));
+                        ui.label(format!(
+                            "{}, {}, {} to {}, {}, {}",
+                            min.x, min.y, min.z, max.x, max.y, max.z,
+                        ));
+                        ui.end_row();
+
+                        ui.strong("Search range");
+                        ui.label(format!(
+                            "±{} blocks horizontally",
+                            destination_dimension.portal_search_range(),
+                        ));
+                        ui.end_row();
+                    });
+
+                ui.separator();
+                ui.strong(format!("Candidates in the {destination_dimension}"));
+                egui::ScrollArea::vertical()
+                    .max_height(300.0)
+                    .show(ui, |ui| {
+                        egui::Grid::new("explain_link_candidates")
+                            .striped(true)
+                            .show(ui, |ui| {
+                                ui.strong("Portal");
+                                ui.strong("Distance");
+                                ui.strong("Result");
+                                ui.end_row();
+                                for candidate in &explanation.candidates {
+                                    let name = self
+                                        .world
+                                        .portals
+                                        .get(candidate.id)
+                                        .map_or("<unknown>", |(_, p)| p.display_name());
+                                    ui.label(name);
+                                    ui.label(format!(
+                                        "{:.1} – {:.1}",
+                                        candidate.min_distance, candidate.max_distance,
+                                    ));
+                                    match candidate.status {
+                                        links::LinkCandidateStatus::Reachable => {
+                                            ui.strong("Links here")
+                                        }
+                                        links::LinkCandidateStatus::OutOfRange => {
+                                            ui.weak("Out of range")
+                                        }
+                                        links::LinkCandidateStatus::AlwaysFarther => {
+                                            ui.weak("Always farther than another portal")
+                                        }
+                                        links::LinkCandidateStatus::NeverClosest => {
+                                            ui.weak("Never the closest")
+                                        }
+                                    };
+                                    ui.end_row();
+                                }
+                            });
+                    });
+
+                if explanation.new_portal {
+                    ui.colored_label(
+                        ui.visuals().error_fg_color,
+                        "Some points in the destination region have no portal in range, \
+                         so a new portal may be generated",
+                    );
+                }
+            });
+        self.explain_link_window_open = open;
+    }
+
+    fn show_route_list(&mut self, ui: &mut egui::Ui, dimension: Dimension) {
+        ui.horizontal(|ui| {
+            ui.strong("Routes");
+            if ui.small_button("Add").clicked() {
+                self.world.routes[dimension].push(Route::default());
+                self.drawing_route = Some((dimension, self.world.routes[dimension].len() - 1));
+            }
+        });
+
+        let mut remove = None;
+        for (i, route) in self.world.routes[dimension].iter_mut().enumerate() {
+            ui.horizontal(|ui| {
+                ui.color_edit_button_srgb(&mut route.color);
+                egui::TextEdit::singleline(&mut route.name)
+                    .hint_text("Route name")
+                    .desired_width(150.0)
+                    .show(ui);
+                let mut drawing = self.drawing_route == Some((dimension, i));
+                if ui
+                    .toggle_value(&mut drawing, "Draw")
+                    .on_hover_text("Click in the plot to add points")
+                    .changed()
+                {
+                    self.drawing_route = drawing.then_some((dimension, i));
+                }
+                if ui
+                    .add_enabled(!route.points.is_empty(), egui::Button::new("Undo Point"))
+                    .clicked()
+                {
+                    route.points.pop();
+                }
+                if img_button(ui, egui::include_image!("img/delete.svg"))
+                    .on_hover_text("Delete route")
+                    .clicked()
+                {
+                    remove = Some(i);
+                }
+            });
+        }
+        if let Some(i) = remove {
+            self.world.routes[dimension].remove(i);
+            self.drawing_route = None;
+        }
+    }
+
+    fn show_area_list(&mut self, ui: &mut egui::Ui, dimension: Dimension) {
+        ui.horizontal(|ui| {
+            ui.strong("Areas");
+            if ui.small_button("Add").clicked() {
+                let center = BlockPos::from(self.camera.pos_in(dimension));
+                let half_size = BlockPos { x: 8, y: 0, z: 8 };
+                let mut region = BlockRegion {
+                    min: center - half_size,
+                    max: center + half_size,
+                };
+                region.min.y = dimension.y_min();
+                region.max.y = dimension.y_max();
+                self.world.areas[dimension].push(Area {
+                    name: String::new(),
+                    color: [255, 255, 255],
+                    region,
+                });
+            }
+        });
+
+        let snap = self.prefs.snap_increment;
+        let mut remove = None;
+        for (i, area) in self.world.areas[dimension].iter_mut().enumerate() {
+            ui.horizontal(|ui| {
+                ui.color_edit_button_srgb(&mut area.color);
+                egui::TextEdit::singleline(&mut area.name)
+                    .hint_text("Area name")
+                    .desired_width(150.0)
+                    .show(ui);
+                if img_button(ui, egui::include_image!("img/delete.svg"))
+                    .on_hover_text("Delete area")
+                    .clicked()
+                {
+                    remove = Some(i);
+                }
+            });
+            show_block_pos_edit(ui, &mut area.region.min, snap);
+            area.region.adjust_max();
+            show_block_pos_edit(ui, &mut area.region.max, snap);
+            area.region.adjust_min();
+        }
+        if let Some(i) = remove {
+            self.world.areas[dimension].remove(i);
+        }
+    }
+
+    fn show_offset_all_modal(&mut self, ctx: &egui::Context) {
+        if let Some((mut dimension, mut offset)) = self.offset_all_modal.take() {
+            let r = egui::Modal::new(egui::Id::new("offset_all")).show(ctx, |ui| {
+                ui.heading("Offset All");
+                ui.label("Move every portal and test point by the same offset.");
+
+                ui.horizontal(|ui| {
+                    ui.label("Offset in");
+                    for dim in [Overworld, Nether] {
+                        ui.selectable_value(&mut dimension, dim, dim.to_string());
+                    }
+                });
+                show_block_pos_edit(ui, &mut offset, self.prefs.snap_increment);
+
+                let other_offset = WorldPos::from(offset)
+                    .convert_dimension(dimension, dimension.other())
+                    .round();
+                ui.weak(format!(
+                    "{} portals move by {}, {}, {}",
+                    dimension.other(),
+                    other_offset.x,
+                    other_offset.y,
+                    other_offset.z,
+                ));
+
+                ui.horizontal(|ui| {
+                    if ui.button("Cancel").clicked() {
+                        ui.close();
+                    }
+                    if ui.button("Apply").clicked() {
+                        self.world.translate(offset.into(), dimension);
+                        ui.close();
+                    }
+                });
+            });
+
+            if !r.should_close() {
+                self.offset_all_modal = Some((dimension, offset));
+            }
+        }
+    }
+
+    fn show_paste_coordinates_modal(&mut self, ctx: &egui::Context) {
+        if let Some(mut text) = self.paste_coordinates_modal.take() {
+            let default_dimension = self.camera.dimension;
+            let r = egui::Modal::new(egui::Id::new("paste_coordinates")).show(ctx, |ui| {
+                ui.heading("Paste Coordinates");
+                ui.label(
+                    "Paste chat messages, /tp commands, or the output of F3+C or /locate, \
+                     with one set of coordinates per line.",
+                );
+                egui::ScrollArea::vertical()
+                    .max_height(200.0)
+                    .show(ui, |ui| {
+                        ui.add(
+                            egui::TextEdit::multiline(&mut text)
+                                .hint_text("[100, 64, -200]")
+                                .desired_width(f32::INFINITY),
+                        );
+                    });
+
+                let found = parse::parse_pasted_coordinates(&text, self.camera.pos.y);
+                ui.weak(format!(
+                    "Found {} coordinates. Lines that don't mention a dimension \
+                     are in the {default_dimension}, and lines in the End are skipped.",
+                    found.len(),
+                ));
+                let positions = found
+                    .into_iter()
+                    .map(|(dimension, mut pos)| {
+                        let dimension = dimension.unwrap_or(default_dimension);
+                        pos.y = dimension.clamp_entity_y(pos.y);
+                        (dimension, pos)
+                    })
+                    .collect_vec();
+
+                ui.horizontal(|ui| {
+                    if ui.button("Cancel").clicked() {
+                        ui.close();
+                    }
+                    ui.add_enabled_ui(!positions.is_empty(), |ui| {
+                        if ui.button("Add Test Points").clicked() {
+                            for &(dimension, pos) in &positions {
+                                self.world.test_points[dimension].push(pos);
+                            }
+                            ui.close();
+                        }
+                        if ui.button("Add Portals").clicked() {
+                            for &(dimension, pos) in &positions {
+                                self.add_portal_at(dimension, pos);
+                            }
+                            ui.close();
+                        }
+                    });
+                });
+            });
+
+            if !r.should_close() {
+                self.paste_coordinates_modal = Some(text);
+            }
+        }
+    }
+
+    fn show_set_corners_modal(&mut self, ctx: &egui::Context) {
+        if let Some((id, mut a, mut b)) = self.set_corners_modal.take() {
+            let Some((dimension, portal)) = self.world.portals.get(id) else {
+                return;
+            };
+            let name = portal.display_name().to_owned();
+            let snap = self.prefs.snap_increment;
+            let r = egui::Modal::new(egui::Id::new("set_corners")).show(ctx, |ui| {
+                ui.heading(format!("Set Corners of {name}"));
+                ui.label("Enter two opposite corners of the portal blocks, not the frame.");
+                show_block_pos_edit(ui, &mut a, snap);
+                show_block_pos_edit(ui, &mut b, snap);
+
+                let result = Portal::region_from_corners(a, b, dimension);
+                match result {
+                    Ok((region, axis)) => {
+                        let w = Axis::from(axis.other());
+                        ui.weak(format!(
+                            "Facing {}, {}×{}",
+                            Axis::from(axis),
+                            region.max[w] - region.min[w] + 1,
+                            region.max.y - region.min.y + 1,
+                        ));
+                    }
+                    Err(e) => {
+                        ui.colored_label(ui.visuals().error_fg_color, e.to_string());
+                    }
+                }
+
+                ui.horizontal(|ui| {
+                    if ui.button("Cancel").clicked() {
+                        ui.close();
+                    }
+                    if ui
+                        .add_enabled(result.is_ok(), egui::Button::new("Apply"))
+                        .clicked()
+                    {
+                        if let Some((dimension, portal)) = self.world.portals.get_mut(id)
+                            && let Err(e) = portal.set_corners(a, b, dimension)
+                        {
+                            log::error!("error setting portal corners: {e}");
+                        }
+                        ui.close();
+                    }
+                });
+            });
+
+            if !r.should_close() {
+                self.set_corners_modal = Some((id, a, b));
+            }
+        }
+    }
+
+    fn show_delete_filtered_modal(&mut self, ctx: &egui::Context) {
+        if let Some((dimension, ids)) = self.delete_filtered_modal.take() {
+            let r = egui::Modal::new(egui::Id::new("delete_filtered")).show(ctx, |ui| {
+                ui.heading("Delete Filtered Portals");
+                ui.label(format!(
+                    "Delete {} {dimension} portals that match the filter?",
+                    ids.len(),
+                ));
+
+                ui.horizontal(|ui| {
+                    if ui.button("Cancel").clicked() {
+                        ui.close();
+                    }
+                    if ui.button("Delete").clicked() {
+                        self.world.portals[dimension].retain(|p| !ids.contains(&p.id));
+                        self.selected_portals.retain(|id| !ids.contains(id));
+                        ui.close();
+                    }
+                });
+            });
+
+            if !r.should_close() {
+                self.delete_filtered_modal = Some((dimension, ids));
+            }
+        }
+    }
+
+    fn show_merge_modal(&mut self, ctx: &egui::Context) {
+        if let Some((mut world, mut dimension, mut offset)) = self.merge_modal.take() {
+            let mut merge = false;
+            let r = egui::Modal::new(egui::Id::new("merge")).show(ctx, |ui| {
+                ui.heading("Merge");
+                let portal_count = world.portals.overworld.len() + world.portals.nether.len();
+                let test_point_count =
+                    world.test_points.overworld.len() + world.test_points.nether.len();
+                ui.label(format!(
+                    "Add {portal_count} portals and {test_point_count} test points \
+                     to the current world.",
+                ));
+
+                ui.label("Offset incoming coordinates by:");
+                ui.horizontal(|ui| {
+                    ui.label("Offset in");
+                    for dim in [Overworld, Nether] {
+                        ui.selectable_value(&mut dimension, dim, dim.to_string());
+                    }
                 });
-            };
+                show_block_pos_edit(ui, &mut offset, self.prefs.snap_increment);
 
-            if collapse_menu {
-                ui.menu_button("Menu", menu_contents);
-            } else {
-                menu_contents(ui);
+                ui.horizontal(|ui| {
+                    if ui.button("Cancel").clicked() {
+                        ui.close();
+                    }
+                    if ui.button("Merge").clicked() {
+                        merge = true;
+                        ui.close();
+                    }
+                });
+            });
+
+            if merge {
+                world.translate(offset.into(), dimension);
+                self.world.merge(world);
+            } else if !r.should_close() {
+                self.merge_modal = Some((world, dimension, offset));
             }
+        }
+    }
 
-            ui.separator();
+    fn show_batch_evaluate_modal(&mut self, ctx: &egui::Context) {
+        if let Some((mut dimension, mut input)) = self.batch_evaluate_modal.take() {
+            let r = egui::Modal::new(egui::Id::new("batch_evaluate")).show(ctx, |ui| {
+                ui.heading("Batch Evaluate");
+                ui.label("Find the portal that an entity arriving at each position would use.");
 
-            let mut camera_controls_contents = |ui: &mut egui::Ui| {
                 ui.horizontal(|ui| {
-                    let mut new_camera_dimension = self.camera.dimension;
+                    ui.label("Positions in");
                     for dim in [Overworld, Nether] {
-                        ui.selectable_value(&mut new_camera_dimension, dim, dim.to_string());
+                        ui.selectable_value(&mut dimension, dim, dim.to_string());
                     }
-                    self.set_camera_dimension(new_camera_dimension);
                 });
 
-                if !collapse_camera_controls {
-                    ui.separator();
+                let max_width = ui.ctx().screen_rect().width() / 2.0;
+                let max_height = ui.ctx().screen_rect().height() / 4.0;
+                ui.strong("Input");
+                ui.weak("One position per line, such as \"x,y,z\"");
+                egui::ScrollArea::vertical()
+                    .id_salt("input")
+                    .max_width(max_width)
+                    .max_height(max_height)
+                    .auto_shrink(false)
+                    .show(ui, |ui| {
+                        egui::TextEdit::multiline(&mut input)
+                            .font(egui::TextStyle::Monospace)
+                            .desired_width(f32::INFINITY)
+                            .show(ui);
+                    });
+                if ui.button("Load CSV…").clicked() {
+                    self.spawn_async_task(async move || {
+                        match rfd::AsyncFileDialog::new()
+                            .add_filter("CSV", &["csv", "txt"])
+                            .pick_file()
+                            .await
+                        {
+                            Some(file_handle) => {
+                                let contents = file_handle.read().await;
+                                Ok(AppAsyncTaskOk::LoadBatchInput {
+                                    text: String::from_utf8_lossy(&contents).into_owned(),
+                                })
+                            }
+                            None => Ok(AppAsyncTaskOk::None),
+                        }
+                    });
                 }
 
-                ui.horizontal(|ui| {
-                    if img_button(ui, egui::include_image!("img/home.svg"))
-                        .on_hover_text("Reset camera")
-                        .clicked()
+                let output = match &self.cached_batch_evaluate_output {
+                    Some(((portals, dim, text), output))
+                        if (portals, *dim, text) == (&self.world.portals, dimension, &input) =>
                     {
-                        self.camera.reset();
+                        output.clone()
+                    }
+                    _ => {
+                        let output =
+                            batch::evaluate_positions(&self.world.portals, dimension, &input);
+                        self.cached_batch_evaluate_output = Some((
+                            (self.world.portals.clone(), dimension, input.clone()),
+                            output.clone(),
+                        ));
+                        output
                     }
+                };
+                ui.strong("Results");
+                egui::ScrollArea::vertical()
+                    .id_salt("output")
+                    .max_width(max_width)
+                    .max_height(max_height)
+                    .auto_shrink(false)
+                    .show(ui, |ui| {
+                        egui::TextEdit::multiline(&mut output.as_str())
+                            .font(egui::TextStyle::Monospace)
+                            .desired_width(f32::INFINITY)
+                            .show(ui);
+                    });
 
-                    show_world_pos_edit(ui, &mut self.camera.pos, Some(0));
+                ui.horizontal(|ui| {
+                    if ui.button("Close").clicked() {
+                        ui.close();
+                    }
+                    if ui.button("Copy").clicked() {
+                        ui.ctx().copy_text(output.clone());
+                    }
+                    if ui.button("Save…").clicked() {
+                        self.export(output, "CSV", "csv");
+                    }
                 });
-            };
+            });
 
-            if collapse_camera_controls {
-                menu_no_autoclose(ui, "Position", camera_controls_contents);
+            if r.should_close() {
+                self.cached_batch_evaluate_output = None;
             } else {
-                camera_controls_contents(ui);
+                self.batch_evaluate_modal = Some((dimension, input));
             }
-
-            ui.separator();
-
-            menu_no_autoclose(ui, "Entity size", |ui| self.show_entity_config(ui));
-
-            ui_unless_overflow(ui, |ui| ui.small(format!("{:#.02}", self.prefs.entity)));
-        });
+        }
     }
 
-    fn show_import_export_modal(&mut self, ctx: &egui::Context) {
-        if let Some(mut text) = self.import_export_modal_text.take() {
-            let r = egui::Modal::new(egui::Id::new("import_export")).show(ctx, |ui| {
-                let r = egui::ScrollArea::vertical()
+    fn show_build_checklist_modal(&mut self, ctx: &egui::Context) {
+        if let Some(text) = self.build_checklist_modal_text.take() {
+            let r = egui::Modal::new(egui::Id::new("build_checklist")).show(ctx, |ui| {
+                ui.heading("Build Checklist");
+                egui::ScrollArea::vertical()
                     .max_width(ui.ctx().screen_rect().width() / 2.0)
-                    .max_height(ui.ctx().screen_rect().height() / 4.0)
+                    .max_height(ui.ctx().screen_rect().height() / 2.0)
                     .auto_shrink(false)
                     .show(ui, |ui| {
-                        ui.with_layout(
-                            egui::Layout::top_down(egui::Align::LEFT)
-                                .with_cross_justify(true)
-                                .with_main_justify(true),
-                            |ui| {
-                                egui::TextEdit::multiline(&mut text)
-                                    .clip_text(false)
-                                    .show(ui)
-                                    .response
-                            },
-                        )
-                        .inner
-                    })
-                    .inner;
-                if r.changed() {
-                    self.cached_import_export_modal_text_deserialized = None;
-                }
-
-                let deserialized = self
-                    .cached_import_export_modal_text_deserialized
-                    .take()
-                    .unwrap_or_else(|| serde_json::from_str(&text));
-
-                match &deserialized {
-                    Ok(_) => ui.label(""),
-                    Err(e) => ui.colored_label(ui.visuals().error_fg_color, e.to_string()),
-                };
+                        egui::TextEdit::multiline(&mut text.as_str())
+                            .font(egui::TextStyle::Monospace)
+                            .desired_width(f32::INFINITY)
+                            .show(ui);
+                    });
 
                 ui.horizontal(|ui| {
-                    if ui.button("Cancel").clicked() {
+                    if ui.button("Close").clicked() {
                         ui.close();
                     }
-
-                    if ui
-                        .add_enabled(deserialized.is_ok(), egui::Button::new("Import"))
-                        .clicked()
-                        && let Ok(world) = &deserialized
-                        && self.is_ok_to_discard_state()
-                    {
-                        self.load(world.clone());
-                        ui.close();
+                    if ui.button("Copy").clicked() {
+                        ui.ctx().copy_text(text.clone());
+                    }
+                    if ui.button("Save…").clicked() {
+                        self.export(text.clone(), "Text", "txt");
                     }
                 });
-
-                self.cached_import_export_modal_text_deserialized = Some(deserialized);
             });
 
-            if r.should_close() {
-                self.cached_import_export_modal_text_deserialized = None;
-            } else {
-                self.import_export_modal_text = Some(text);
+            if !r.should_close() {
+                self.build_checklist_modal_text = Some(text);
             }
         }
     }
 }
 
 impl eframe::App for App {
+    fn raw_input_hook(&mut self, _ctx: &egui::Context, raw_input: &mut egui::RawInput) {
+        for event in &mut raw_input.events {
+            match event {
+                egui::Event::MouseWheel {
+                    delta, modifiers, ..
+                } => {
+                    *delta *= if modifiers.command || modifiers.ctrl {
+                        self.prefs.zoom_sensitivity
+                    } else {
+                        self.prefs.scroll_sensitivity
+                    };
+                }
+                egui::Event::Zoom(factor) => *factor = factor.powf(self.prefs.zoom_sensitivity),
+                _ => (),
+            }
+        }
+    }
+
     fn save(&mut self, storage: &mut dyn eframe::Storage) {
         match serde_json::to_string_pretty(&self.prefs) {
             Ok(prefs_str) => storage.set_string(Preferences::STORAGE_KEY, prefs_str),
             Err(e) => log::error!("error saving preferences: {e}"),
         }
+
+        #[cfg(target_arch = "wasm32")]
+        {
+            // An empty string means there is no world to restore.
+            let world_str = if self.prefs.autosave {
+                self.world
+                    .to_json(true)
+                    .inspect_err(|e| log::error!("error saving world: {e}"))
+                    .unwrap_or_default()
+            } else {
+                String::new()
+            };
+            storage.set_string(Self::WORLD_STORAGE_KEY, world_str);
+        }
+    }
+
+    fn on_exit(&mut self, _gl: Option<&eframe::glow::Context>) {
+        // The user has already confirmed discarding any unsaved changes.
+        #[cfg(not(target_arch = "wasm32"))]
+        self.recovery_file.remove();
     }
 
     fn update(&mut self, ctx: &eframe::egui::Context, _frame: &mut eframe::Frame) {
@@ -1385,6 +4632,21 @@ impl eframe::App for App {
             ctx.send_viewport_cmd(egui::ViewportCommand::CancelClose);
         }
 
+        self.update_theme(ctx);
+
+        // The clipboard contents requested last frame arrive as a paste event.
+        if let Some(id) = self.paste_coordinates_target.take() {
+            let pasted_text = ctx.input(|input| {
+                input.events.iter().find_map(|event| match event {
+                    egui::Event::Paste(text) => Some(text.clone()),
+                    _ => None,
+                })
+            });
+            if let Some(text) = pasted_text {
+                self.paste_portal_coordinates(id, &text);
+            }
+        }
+
         // Disable the whole UI if there is a dialog open.
         let mut disable_everything = false;
         if let Some(async_task) = self.async_task.take() {
@@ -1409,12 +4671,31 @@ impl eframe::App for App {
                         self.load(world);
                         self.prefs.file_path = path;
                     }
+                    AppAsyncTaskOk::Merge { world } => {
+                        self.merge_modal =
+                            Some((world, self.camera.dimension, BlockPos::default()));
+                    }
+                    AppAsyncTaskOk::Compare { world } => {
+                        let diff = diff::WorldDiff::new(&self.world, &world);
+                        self.comparison = Some((world, diff));
+                    }
+                    #[cfg(not(target_arch = "wasm32"))]
+                    AppAsyncTaskOk::SetLivePositionFile { path } => {
+                        self.live_position = Some(live::LivePosition::new(path));
+                    }
+                    AppAsyncTaskOk::LoadBatchInput { text } => {
+                        if let Some((_, input)) = &mut self.batch_evaluate_modal {
+                            *input = text;
+                        }
+                    }
                 },
                 // async task failed
                 Ok(Err(e)) => show_error_dialog(e),
             }
         }
 
+        self.links_stale = self.are_links_stale();
+
         egui_extras::install_image_loaders(ctx); // ok to call every frame
 
         egui::TopBottomPanel::top("menu_bar").show(ctx, |ui| {
@@ -1481,12 +4762,15 @@ impl eframe::App for App {
 
             self.portals_hovered.in_plot =
                 std::mem::take(&mut self.portals_hovered.in_plot_for_next_frame);
+            self.link_preview = self.link_preview_for_next_frame.take();
+            self.portal_lookup.update(&self.world.portals);
+            self.update_link_entities();
             for (plane, rect) in [
                 (Plane::XY, left_bottom),
                 (Plane::ZY, right_bottom),
                 (Plane::XZ, left_top),
             ] {
-                if !self.prefs.show_zy_plot && plane == Plane::ZY {
+                if !self.show_zy_plot() && plane == Plane::ZY {
                     continue;
                 }
                 ui.put(rect, |ui: &mut egui::Ui| {
@@ -1494,7 +4778,32 @@ impl eframe::App for App {
                         .response
                 });
             }
+
+            // Toggle for the ZY plot in the corner of the XY plot
+            let size = vec2(56.0, 18.0);
+            let toggle_rect = Rect::from_min_size(
+                left_bottom.right_top() + vec2(-size.x - PLOT_MARGIN * 2.0, PLOT_MARGIN * 2.0),
+                size,
+            );
+            let toggle_text = if self.show_zy_plot() {
+                "Hide ZY"
+            } else {
+                "Show ZY"
+            };
+            if ui
+                .put(toggle_rect, egui::Button::new(toggle_text).small())
+                .on_hover_text("Show or hide the ZY plot")
+                .clicked()
+            {
+                self.set_show_zy_plot(!self.show_zy_plot());
+            }
+
             self.camera = new_camera;
+            #[cfg(not(target_arch = "wasm32"))]
+            if let Some(live_position) = &mut self.live_position {
+                live_position.poll();
+                ctx.request_repaint_after(live::POLL_INTERVAL);
+            }
             let now = web_time::Instant::now();
             if !self.animation_state.is_static() {
                 ctx.request_repaint();
@@ -1503,7 +4812,7 @@ impl eframe::App for App {
                 .step((now - self.animation_state.last_frame).as_secs_f64());
             self.animation_state.last_frame = now;
 
-            let controls_rect = if self.prefs.show_zy_plot {
+            let controls_rect = if self.show_zy_plot() {
                 right_top
             } else {
                 right_top.union(right_bottom)
@@ -1515,18 +4824,43 @@ impl eframe::App for App {
             let is_text_field_active = ui.ctx().wants_keyboard_input();
             ui.input_mut(|input| {
                 if !input.pointer.is_decidedly_dragging() && !is_text_field_active {
+                    // Expanding or collapsing portals and changing the layout
+                    // or display settings are saved but do not create an undo
+                    // step.
+                    if self.last_frame_state.expanded_portals != self.world.expanded_portals
+                        || self.last_frame_state.display != self.world.display
+                        || self.last_frame_state.show_zy_plot != self.world.show_zy_plot
+                    {
+                        self.unsaved_changes = true;
+                        self.last_frame_state.expanded_portals =
+                            self.world.expanded_portals.clone();
+                        self.last_frame_state.display = self.world.display;
+                        self.last_frame_state.show_zy_plot = self.world.show_zy_plot;
+                    }
                     if self.last_frame_state != self.world {
                         self.unsaved_changes = true;
                         let old_state =
                             std::mem::replace(&mut self.last_frame_state, self.world.clone());
                         self.redo_history.clear();
-                        self.undo_history.push(old_state);
+                        self.undo_history.push(HistoryEntry::Edit(old_state));
                     }
                     #[cfg(not(target_arch = "wasm32"))]
                     if self.prefs.autosave && self.unsaved_changes && self.prefs.file_path.is_some()
                     {
                         self.save();
                     }
+                    #[cfg(not(target_arch = "wasm32"))]
+                    if self.unsaved_changes {
+                        self.recovery_file
+                            .write(&self.world, self.prefs.file_path.as_deref());
+                    } else {
+                        self.recovery_file.remove();
+                    }
+
+                    // Ignore shortcuts while a dialog is open
+                    if disable_everything {
+                        return;
+                    }
 
                     // Consume the most specific shortcut first
                     if input.consume_shortcut(&kbd_shortcuts::CMD_SHIFT_Z)
@@ -1542,7 +4876,19 @@ impl eframe::App for App {
                     }
 
                     if input.consume_shortcut(&kbd_shortcuts::RESET_CAMERA) {
-                        self.camera.reset();
+                        self.reset_camera();
+                    }
+
+                    if input.consume_shortcut(&kbd_shortcuts::ADD_PORTAL) {
+                        self.add_portal(self.camera.dimension);
+                    }
+
+                    for ((_, entity), shortcut) in
+                        std::iter::zip(ENTITY_PRESETS, &kbd_shortcuts::ENTITY_PRESETS)
+                    {
+                        if input.consume_shortcut(shortcut) {
+                            self.prefs.entity = entity;
+                        }
                     }
 
                     if input.consume_shortcut(&kbd_shortcuts::NEW) {
@@ -1569,32 +4915,142 @@ impl eframe::App for App {
         });
 
         self.show_import_export_modal(ctx);
-
-        let (cached_world, cached_entity) = &self.cached_state;
-        if (cached_world, cached_entity) != (&self.world, &self.prefs.entity) {
+        self.show_build_checklist_modal(ctx);
+        self.show_batch_evaluate_modal(ctx);
+        self.show_offset_all_modal(ctx);
+        self.show_paste_coordinates_modal(ctx);
+        self.show_set_corners_modal(ctx);
+        self.show_merge_modal(ctx);
+        self.show_delete_filtered_modal(ctx);
+        self.show_annotations_window(ctx);
+        self.show_explain_link_window(ctx);
+        self.show_go_to_window(ctx);
+        self.show_stats_window(ctx);
+        self.show_templates_window(ctx);
+
+        if self.are_links_stale() {
             let t = web_time::Instant::now();
             self.cached_state = (self.world.clone(), self.prefs.entity);
             self.recalculate_portal_links();
             log::debug!("Recalculated portal links in {:?}", t.elapsed());
+            // The world may have changed, so compare it again.
+            if let Some((other_world, diff)) = &mut self.comparison {
+                *diff = diff::WorldDiff::new(&self.world, other_world);
+            }
+            // Show the new links, which were calculated after drawing.
+            ctx.request_repaint();
+        }
+    }
+}
+
+/// Draws a scale bar at the bottom of a plot, with a length in blocks that
+/// is a round number.
+fn show_scale_bar(ui: &egui::Ui, plot_frame: egui::Rect, bounds_width: f64) {
+    /// Approximate maximum length of the scale bar, in points.
+    const MAX_LENGTH: f32 = 120.0;
+
+    let points_per_block = plot_frame.width() as f64 / bounds_width;
+    let max_blocks = MAX_LENGTH as f64 / points_per_block;
+    let power_of_ten = 10.0_f64.powf(max_blocks.log10().floor());
+    let Some(blocks) = [5.0, 2.0, 1.0]
+        .map(|m| m * power_of_ten)
+        .into_iter()
+        .find(|&b| b <= max_blocks)
+    else {
+        return;
+    };
+    let length = (blocks * points_per_block) as f32;
+
+    let visuals = ui.visuals();
+    let stroke = egui::Stroke::new(1.5, visuals.text_color());
+    let painter = ui.painter_at(plot_frame);
+    let y = plot_frame.bottom() - PLOT_MARGIN * 2.0;
+    let x_min = plot_frame.center().x - length / 2.0;
+    let x_max = plot_frame.center().x + length / 2.0;
+    painter.hline(x_min..=x_max, y, stroke);
+    for x in [x_min, x_max] {
+        painter.vline(x, y - 4.0..=y, stroke);
+    }
+    painter.text(
+        egui::pos2(plot_frame.center().x, y - 2.0),
+        egui::Align2::CENTER_BOTTOM,
+        if blocks == 1.0 {
+            "1 block".to_owned()
+        } else {
+            format!("{blocks} blocks")
+        },
+        egui::FontId::proportional(12.0),
+        visuals.text_color(),
+    );
+}
+
+/// Draws text labels in a plot, moving them vertically to avoid overlapping
+/// each other. Earlier labels take priority. Labels that are moved have a line
+/// drawn to their original position.
+fn show_labels_in_plot(
+    plot_ui: &mut egui_plot::PlotUi<'_>,
+    labels: Vec<(egui_plot::PlotPoint, egui::text::LayoutJob)>,
+    painter: &egui::Painter,
+    points_per_value: egui::Vec2,
+) {
+    /// Maximum number of label heights to move a label up or down.
+    const MAX_LABEL_OFFSET: i32 = 4;
+
+    let mut placed_rects: Vec<egui::Rect> = vec![];
+    for (anchor, job) in labels {
+        let size = painter.layout_job(job.clone()).size() / points_per_value;
+        let anchor_pos = egui::pos2(anchor.x as f32, anchor.y as f32);
+        let rect_at_offset = |i: i32| {
+            egui::Rect::from_center_size(anchor_pos + egui::vec2(0.0, i as f32 * size.y), size)
+        };
+        let offset = (0..=MAX_LABEL_OFFSET)
+            .flat_map(|i| [i, -i])
+            .find(|&i| !placed_rects.iter().any(|r| r.intersects(rect_at_offset(i))))
+            .unwrap_or(0);
+        placed_rects.push(rect_at_offset(offset));
+
+        let pos = egui_plot::PlotPoint::new(anchor.x, anchor.y + (offset as f32 * size.y) as f64);
+        if offset != 0 {
+            let mut line = egui_plot::Line::new("", vec![[anchor.x, anchor.y], [pos.x, pos.y]]);
+            if let Some(section) = job.sections.first() {
+                line = line.color(section.format.color.gamma_multiply(0.5));
+            }
+            plot_ui.add(line);
         }
+        plot_ui.add(egui_plot::Text::new("", pos, job));
     }
 }
 
-fn show_block_pos_edit(ui: &mut egui::Ui, BlockPos { x, y, z }: &mut BlockPos) {
+fn clamp_to_range(x: f32, range: RangeInclusive<f32>) -> f32 {
+    x.clamp(*range.start(), *range.end())
+}
+
+/// Shows drag values for block coordinates, which snap to multiples of
+/// `snap_increment` while dragging with Alt held.
+fn show_block_pos_edit(
+    ui: &mut egui::Ui,
+    BlockPos { x, y, z }: &mut BlockPos,
+    snap_increment: i64,
+) {
     ui.horizontal(|ui| {
-        dv_i64(ui, "X", x);
-        dv_i64(ui, "Y", y);
-        dv_i64(ui, "Z", z);
+        dv_i64(ui, "X", x, snap_increment);
+        dv_i64(ui, "Y", y, snap_increment);
+        dv_i64(ui, "Z", z, snap_increment);
     });
 }
 
+/// Shows drag values for a position in `dimension`, which limits the Y
+/// coordinate.
 fn show_world_pos_edit(
     ui: &mut egui::Ui,
     WorldPos { x, y, z }: &mut WorldPos,
+    dimension: Dimension,
     fixed_decimals: Option<usize>,
 ) -> egui::Response {
     let make_drag_value = |value| {
-        let dv = egui::DragValue::new(value).speed(0.1);
+        let dv = egui::DragValue::new(value)
+            .speed(0.1)
+            .update_while_editing(false);
         match fixed_decimals {
             Some(num_decimals) => dv.fixed_decimals(num_decimals),
             None => dv,
@@ -1603,26 +5059,73 @@ fn show_world_pos_edit(
 
     ui.horizontal(|ui| {
         coordinate_label(ui, "X");
-        ui.add(make_drag_value(x));
+        let r = ui.add(make_drag_value(x));
+        keep_focus_on_enter(ui, &r);
 
         coordinate_label(ui, "Y");
-        ui.add(make_drag_value(y).range(Overworld.y_min()..=Overworld.y_max() + 1));
+        let r = ui.add(make_drag_value(y).range(dimension.entity_y_range()));
+        keep_focus_on_enter(ui, &r);
 
         coordinate_label(ui, "Z");
-        ui.add(make_drag_value(z));
+        let r = ui.add(make_drag_value(z));
+        keep_focus_on_enter(ui, &r);
     })
     .response
 }
 
-fn dv_i64(ui: &mut egui::Ui, label: &str, i: &mut i64) -> egui::Response {
+fn dv_i64(ui: &mut egui::Ui, label: &str, i: &mut i64, snap_increment: i64) -> egui::Response {
     ui.horizontal(|ui| {
         coordinate_label(ui, label);
-        egui::DragValue::new(i)
+        let r = egui::DragValue::new(i)
             .speed(0.125)
             .update_while_editing(false)
             .ui(ui);
+        if r.dragged() && ui.input(|input| input.modifiers.alt) {
+            *i = snap_to_multiple(*i, snap_increment);
+        }
+        keep_focus_on_enter(ui, &r);
+        r
     })
-    .response
+    .inner
+}
+
+/// Shows drag values for block coordinates in `dimension` converted to the
+/// other dimension. Only the axes that are edited are converted back, so that
+/// rounding does not move the others.
+fn show_converted_block_pos_edit(
+    ui: &mut egui::Ui,
+    pos: &mut BlockPos,
+    dimension: Dimension,
+    snap_increment: i64,
+) {
+    let old_converted = WorldPos::from(*pos)
+        .convert_dimension(dimension, dimension.other())
+        .floor();
+    let mut converted = old_converted;
+    show_block_pos_edit(ui, &mut converted, snap_increment);
+    let new_pos = WorldPos::from(converted)
+        .convert_dimension(dimension.other(), dimension)
+        .floor();
+    for axis in Axis::ALL {
+        if converted[axis] != old_converted[axis] {
+            pos[axis] = new_pos[axis];
+        }
+    }
+}
+
+/// Rounds `value` to the nearest multiple of `increment`.
+fn snap_to_multiple(value: i64, increment: i64) -> i64 {
+    let increment = increment.max(1);
+    (value + increment / 2).div_euclid(increment) * increment
+}
+
+/// Keeps keyboard focus on a text field after Enter is pressed to commit its
+/// value, so that Tab still moves on to the next field instead of starting
+/// over from the top of the window.
+fn keep_focus_on_enter(ui: &egui::Ui, r: &egui::Response) {
+    if r.lost_focus() && ui.input(|input| input.key_pressed(egui::Key::Enter)) {
+        r.request_focus();
+    }
 }
 
 fn coordinate_label(ui: &mut egui::Ui, text: &str) -> egui::Response {
@@ -1633,26 +5136,52 @@ fn coordinate_label(ui: &mut egui::Ui, text: &str) -> egui::Response {
 
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
 enum PortalLinkResult {
-    EntityWontFit,
+    EntityWontFit(EntityWontFit),
     Portals {
         ids: Vec<PortalId>,
         new_portal: bool,
     },
 }
+impl PortalLinkResult {
+    /// Returns whether no existing portal is in range, so that a new portal is
+    /// always generated. This usually means that the portal's counterpart
+    /// hasn't been built.
+    fn is_isolated(&self) -> bool {
+        matches!(self, PortalLinkResult::Portals { ids, new_portal: true } if ids.is_empty())
+    }
+}
+
+/// Returns the ID of the collapsing header for a portal in the portal list.
+fn portal_header_id(id: PortalId) -> egui::Id {
+    egui::Id::new(id).with("header")
+}
 
 fn show_link_result(
     ui: &mut egui::Ui,
     result: Option<&(PortalLinkResult, Vec<PortalId>)>,
     portals_by_id: &HashMap<PortalId, Portal>,
+    stale: bool,
 ) {
     let Some((outgoing, incoming)) = result else {
         ui.colored_label(ui.visuals().warn_fg_color, "Calculating ...");
         return;
     };
 
+    if stale {
+        ui.horizontal(|ui| {
+            ui.spinner();
+            ui.weak("Recalculating ...");
+        });
+        ui.scope(|ui| {
+            ui.multiply_opacity(0.5);
+            show_link_result(ui, result, portals_by_id, false);
+        });
+        return;
+    }
+
     match outgoing {
-        PortalLinkResult::EntityWontFit => {
-            ui.colored_label(ui.visuals().error_fg_color, "Entity won't fit");
+        PortalLinkResult::EntityWontFit(reason) => {
+            ui.colored_label(ui.visuals().error_fg_color, reason.to_string());
         }
         PortalLinkResult::Portals { ids, new_portal } => {
             if !ids.is_empty() {
@@ -1660,7 +5189,10 @@ fn show_link_result(
                 push_portal_list_text(ui, &mut label_atoms, ids, portals_by_id);
                 ui.add(egui::AtomLayout::new(label_atoms));
             }
-            if *new_portal {
+            if outgoing.is_isolated() {
+                ui.colored_label(ui.visuals().error_fg_color, "Always generates new portal")
+                    .on_hover_text("No existing portal is in range");
+            } else if *new_portal {
                 ui.colored_label(ui.visuals().error_fg_color, "Generates new portal");
             }
         }
@@ -1673,6 +5205,38 @@ fn show_link_result(
     }
 }
 
+/// Shows an icon summarizing where a portal links: a check mark if it links
+/// to exactly one portal, a warning if it may link to more than one, a cross if
+/// the entity won't fit or a new portal may be generated, and a prohibition
+/// sign if no existing portal is in range.
+fn show_link_status_icon(ui: &mut egui::Ui, outgoing: &PortalLinkResult) {
+    let visuals = ui.visuals();
+    let (icon, color, hover_text) = match outgoing {
+        PortalLinkResult::EntityWontFit(reason) => {
+            ("✖", visuals.error_fg_color, reason.to_string())
+        }
+        _ if outgoing.is_isolated() => (
+            "🚫",
+            visuals.error_fg_color,
+            "Always generates new portal because no existing portal is in range".to_owned(),
+        ),
+        PortalLinkResult::Portals {
+            new_portal: true, ..
+        } => (
+            "✖",
+            visuals.error_fg_color,
+            "Generates new portal".to_owned(),
+        ),
+        PortalLinkResult::Portals { ids, .. } if ids.len() > 1 => (
+            "⚠",
+            visuals.warn_fg_color,
+            format!("May link to any of {} portals", ids.len()),
+        ),
+        PortalLinkResult::Portals { .. } => ("✔", LINK_OK_COLOR, "Links to one portal".to_owned()),
+    };
+    ui.colored_label(color, icon).on_hover_text(hover_text);
+}
+
 fn push_portal_list_text(
     ui: &egui::Ui,
     atoms: &mut egui::Atoms<'_>,
@@ -1697,16 +5261,20 @@ fn push_portal_list_text(
     }
 }
 
-#[derive(Debug, Copy, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq)]
 struct AnimationState {
     last_frame: web_time::Instant,
     aspect_ratio_scale: f64,
+    /// Remaining highlight, from 0 to 1, on each portal whose link recently
+    /// changed.
+    link_changed: HashMap<PortalId, f64>,
 }
 impl Default for AnimationState {
     fn default() -> Self {
         Self {
             last_frame: web_time::Instant::now(),
             aspect_ratio_scale: 1.0,
+            link_changed: HashMap::new(),
         }
     }
 }
@@ -1716,18 +5284,101 @@ impl AnimationState {
         if self.aspect_ratio_scale.log2().abs() < 0.0025 {
             self.aspect_ratio_scale = 1.0;
         }
+        self.link_changed.retain(|_, t| {
+            *t -= dt / LINK_CHANGED_FLASH_DURATION;
+            *t > 0.0
+        });
     }
 
     fn is_static(&self) -> bool {
-        self.aspect_ratio_scale == 1.0
+        self.aspect_ratio_scale == 1.0 && self.link_changed.is_empty()
+    }
+
+    /// Returns the color of the highlight on a portal whose link recently
+    /// changed.
+    fn link_changed_color(&self, id: PortalId) -> Option<egui::Color32> {
+        let t = *self.link_changed.get(&id)?;
+        Some(LINK_CHANGED_COLOR.gamma_multiply(t as f32))
     }
 }
 
+/// Direction of links to show arrows for.
 #[derive(Serialize, Deserialize, Debug, Default, Copy, Clone, PartialEq, Eq, Hash)]
-enum ArrowColoring {
+enum LinkDirection {
     #[default]
-    BySource,
-    ByDestination,
+    Both,
+    Outgoing,
+    Incoming,
+}
+impl LinkDirection {
+    fn includes_outgoing(self) -> bool {
+        self != LinkDirection::Incoming
+    }
+    fn includes_incoming(self) -> bool {
+        self != LinkDirection::Outgoing
+    }
+}
+
+impl From<Theme> for egui::ThemePreference {
+    fn from(theme: Theme) -> Self {
+        match theme {
+            Theme::System => egui::ThemePreference::System,
+            Theme::Dark => egui::ThemePreference::Dark,
+            Theme::Light => egui::ThemePreference::Light,
+        }
+    }
+}
+impl From<egui::ThemePreference> for Theme {
+    fn from(theme: egui::ThemePreference) -> Self {
+        match theme {
+            egui::ThemePreference::System => Theme::System,
+            egui::ThemePreference::Dark => Theme::Dark,
+            egui::ThemePreference::Light => Theme::Light,
+        }
+    }
+}
+
+/// Property by which to sort a portal list.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+enum PortalSortKey {
+    Name,
+    X,
+    Z,
+    DistanceFromCamera,
+    LinkStatus,
+}
+impl PortalSortKey {
+    const ALL: [Self; 5] = [
+        Self::Name,
+        Self::X,
+        Self::Z,
+        Self::DistanceFromCamera,
+        Self::LinkStatus,
+    ];
+
+    fn label(self) -> &'static str {
+        match self {
+            PortalSortKey::Name => "By Name",
+            PortalSortKey::X => "By X",
+            PortalSortKey::Z => "By Z",
+            PortalSortKey::DistanceFromCamera => "By Distance from Camera",
+            PortalSortKey::LinkStatus => "By Link Status",
+        }
+    }
+}
+
+/// State in the undo or redo history.
+#[derive(Debug, Clone)]
+enum HistoryEntry {
+    /// World before or after an edit.
+    Edit(World),
+    /// World before or after loading a file, along with the path of the file
+    /// that it belongs to, which is restored along with the world so that
+    /// saving does not overwrite the wrong file.
+    Load {
+        world: World,
+        file_path: Option<PathBuf>,
+    },
 }
 
 #[derive(Debug, Default, Clone)]
@@ -1736,6 +5387,42 @@ struct PortalHoverState {
     in_plot: Vec<PortalId>,
     in_plot_for_next_frame: Vec<PortalId>,
 }
+/// Filter for the portals shown in a portal list.
+#[derive(Debug, Default, Clone)]
+struct PortalFilter {
+    /// Text that portal names must contain, ignoring case.
+    name: String,
+    /// Whether to show only portals that no portal links to.
+    orphans_only: bool,
+}
+impl PortalFilter {
+    fn is_active(&self) -> bool {
+        !self.name.is_empty() || self.orphans_only
+    }
+
+    fn matches(
+        &self,
+        portal: &Portal,
+        links: &HashMap<PortalId, (PortalLinkResult, Vec<PortalId>)>,
+    ) -> bool {
+        let name_matches = (portal.name.to_lowercase()).contains(&self.name.to_lowercase());
+        let is_orphan = links
+            .get(&portal.id)
+            .is_some_and(|(_, incoming)| incoming.is_empty());
+        name_matches && (!self.orphans_only || is_orphan)
+    }
+}
+
+/// Drag of the selected portals in one of the plots.
+#[derive(Debug, Clone)]
+struct PortalDrag {
+    plane: Plane,
+    /// Position where the drag started, in the camera's dimension.
+    start: WorldPos,
+    /// Dimension, ID, and original region of each portal being dragged.
+    portals: Vec<(Dimension, PortalId, BlockRegion)>,
+}
+
 impl PortalHoverState {
     fn is_empty(&self) -> bool {
         self.in_list.is_none() && self.in_plot.is_empty()
@@ -1745,6 +5432,16 @@ impl PortalHoverState {
     }
 }
 
+/// Shows a faint crosshair at `point`, such as the camera position.
+fn show_crosshair_in_plot(plot_ui: &mut egui_plot::PlotUi<'_>, point: egui_plot::PlotPoint) {
+    plot_ui.add(
+        egui_plot::Points::new("", egui_plot::PlotPoints::Owned(vec![point]))
+            .shape(egui_plot::MarkerShape::Plus)
+            .radius(CROSSHAIR_RADIUS)
+            .color(egui::Color32::GRAY.gamma_multiply(0.6)),
+    );
+}
+
 fn show_credits(ui: &mut egui::Ui) {
     ui.label(format!("{TITLE} v{} by ", env!("CARGO_PKG_VERSION")));
     ui.hyperlink_to("Andrew Farkas", "https://ajfarkas.dev/");
@@ -1826,6 +5523,15 @@ enum AppAsyncTaskOk {
     MarkSaved { path: Option<PathBuf> },
     /// Load world from file.
     Load { path: Option<PathBuf>, world: World },
+    /// Merge world from file into the current one.
+    Merge { world: World },
+    /// Compare the current world against a world from file.
+    Compare { world: World },
+    /// Use text from file as input for the "Batch Evaluate" modal.
+    LoadBatchInput { text: String },
+    /// Start reading the player's position from a file.
+    #[cfg(not(target_arch = "wasm32"))]
+    SetLivePositionFile { path: PathBuf },
 }
 /// Error message dialog to display before re-enabling the UI.
 struct AppAsyncTaskErr {
@@ -1841,6 +5547,42 @@ impl<T: ToString, D: ToString> From<(T, D)> for AppAsyncTaskErr {
     }
 }
 
+/// Asks the user to pick a JSON file and deserializes a world from it.
+///
+/// Returns `None` if the user cancels.
+async fn pick_world_file() -> Result<Option<(rfd::FileHandle, World)>, AppAsyncTaskErr> {
+    let Some(file_handle) = rfd::AsyncFileDialog::new()
+        .add_filter("JSON", &["json"])
+        .pick_file()
+        .await
+    else {
+        return Ok(None);
+    };
+    let contents = file_handle.read().await;
+    let (world, duplicate_ids) =
+        World::from_json_with_duplicate_ids(&String::from_utf8_lossy(&contents))
+            .map_err(|e| ("Error deserializing file", e))?;
+    if !duplicate_ids.is_empty() {
+        rfd::AsyncMessageDialog::new()
+            .set_level(rfd::MessageLevel::Warning)
+            .set_title("Duplicate portal IDs")
+            .set_description(duplicate_ids_warning(&duplicate_ids))
+            .show()
+            .await;
+    }
+    Ok(Some((file_handle, world)))
+}
+
+/// Returns a warning about portal IDs that are used by more than one portal in
+/// a file.
+fn duplicate_ids_warning(duplicate_ids: &[u64]) -> String {
+    format!(
+        "Portal IDs {} are used by more than one portal. \
+         Only the first portal with each ID keeps it along with its intended links.",
+        duplicate_ids.iter().join(", "),
+    )
+}
+
 fn show_error_dialog(e: impl Into<AppAsyncTaskErr>) {
     let e = e.into();
     rfd::MessageDialog::new()
@@ -1849,3 +5591,25 @@ fn show_error_dialog(e: impl Into<AppAsyncTaskErr>) {
         .set_description(e.description)
         .show();
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_world_pos_edit_clamps_y() {
+        let ctx = egui::Context::default();
+        let edit = |dimension, y| {
+            let mut pos = WorldPos { x: 0.0, y, z: 0.0 };
+            let _ = ctx.run(egui::RawInput::default(), |ctx| {
+                egui::CentralPanel::default().show(ctx, |ui| {
+                    show_world_pos_edit(ui, &mut pos, dimension, None);
+                });
+            });
+            pos.y
+        };
+        assert_eq!(edit(Nether, 200.0), 200.0);
+        assert_eq!(edit(Nether, 300.0), 256.0);
+        assert_eq!(edit(Overworld, 300.0), 300.0);
+    }
+}
@@ -0,0 +1,167 @@
+//! Parsing of coordinates copied from the game or from chat.
+
+use itertools::Itertools;
+
+use crate::{Dimension, Nether, Overworld, WorldPos};
+
+/// Parses the first three numbers in `text` as X, Y, and Z coordinates.
+///
+/// This accepts `x y z`, `x, y, z`, and lines such as `XYZ: x / y / z` from
+/// the F3 debug screen or `/tp @s x y z` commands. Words that contain digits,
+/// such as player names, are skipped.
+pub fn parse_coordinates(text: &str) -> Option<WorldPos> {
+    let mut numbers = text
+        .split(|c: char| c.is_whitespace() || ",/[](){}".contains(c))
+        .filter_map(|word| word.parse::<f64>().ok())
+        .filter(|n| n.is_finite());
+    Some(WorldPos {
+        x: numbers.next()?,
+        y: numbers.next()?,
+        z: numbers.next()?,
+    })
+}
+
+/// Parses every line of `text` that contains coordinates, along with the
+/// dimension mentioned on that line, if any. Lines in other dimensions, such as
+/// the End, are skipped.
+///
+/// Bracketed coordinates such as `[12, 64, -30]` in chat messages take
+/// priority over other numbers on the same line. The output of `/locate`, such
+/// as `[-232, ~, 104]`, has no Y coordinate, so `default_y` is used instead.
+pub fn parse_pasted_coordinates(text: &str, default_y: f64) -> Vec<(Option<Dimension>, WorldPos)> {
+    text.lines()
+        .filter(|line| !mentions_unsupported_dimension(line))
+        .filter_map(|line| {
+            let pos = line
+                .split('[')
+                .skip(1)
+                .find_map(|s| parse_bracketed_coordinates(s.split(']').next()?, default_y))
+                .or_else(|| parse_coordinates(line))?;
+            Some((parse_dimension(line), pos))
+        })
+        .collect()
+}
+
+/// Parses `x, y, z` from between square brackets. `~` is accepted for the Y
+/// coordinate and replaced with `default_y`.
+fn parse_bracketed_coordinates(text: &str, default_y: f64) -> Option<WorldPos> {
+    let parse = |s: &str| s.trim().parse::<f64>().ok().filter(|n| n.is_finite());
+    let mut parts = text.split(',');
+    let (x, y, z) = (parts.next()?, parts.next()?, parts.next()?);
+    if parts.next().is_some() {
+        return None;
+    }
+    Some(WorldPos {
+        x: parse(x)?,
+        y: if y.trim() == "~" {
+            default_y
+        } else {
+            parse(y)?
+        },
+        z: parse(z)?,
+    })
+}
+
+/// Returns the dimension whose ID is mentioned in `text`, such as
+/// `minecraft:the_nether` in the output of F3+C, or `None` if no dimension ID
+/// is mentioned.
+pub fn parse_dimension(text: &str) -> Option<Dimension> {
+    text.split(|c: char| c.is_whitespace() || ",\"'[](){}".contains(c))
+        .find_map(|word| match word.to_ascii_lowercase().as_str() {
+            "minecraft:overworld" => Some(Overworld),
+            "minecraft:the_nether" => Some(Nether),
+            _ => None,
+        })
+}
+
+/// Returns whether `text` mentions a dimension other than the overworld and
+/// the nether, such as `minecraft:the_end` or a modded dimension in the output
+/// of F3+C.
+pub fn mentions_unsupported_dimension(text: &str) -> bool {
+    let text = text.to_ascii_lowercase();
+    let execute_in_dimension = text
+        .split_whitespace()
+        .tuple_windows()
+        .find_map(|(a, b, c)| (a.ends_with("execute") && b == "in").then_some(c));
+    text.contains("the_end")
+        || execute_in_dimension
+            .is_some_and(|id| !matches!(id, "minecraft:overworld" | "minecraft:the_nether"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_coordinates() {
+        let pos = |x, y, z| Some(WorldPos { x, y, z });
+        assert_eq!(parse_coordinates("12 64 -30"), pos(12.0, 64.0, -30.0));
+        assert_eq!(parse_coordinates("12, 64, -30"), pos(12.0, 64.0, -30.0));
+        assert_eq!(parse_coordinates("[12, 64, -30]"), pos(12.0, 64.0, -30.0));
+        assert_eq!(
+            parse_coordinates("XYZ: 12.345 / 64.00000 / -30.5"),
+            pos(12.345, 64.0, -30.5),
+        );
+        assert_eq!(
+            parse_coordinates("Block: 12 64 -31 [12 0 1]"),
+            pos(12.0, 64.0, -31.0),
+        );
+        assert_eq!(
+            parse_coordinates("/execute in minecraft:the_nether run tp Steve2 1 2 3"),
+            pos(1.0, 2.0, 3.0),
+        );
+        assert_eq!(parse_coordinates("12 64"), None);
+        assert_eq!(parse_coordinates("~ ~1 ~"), None);
+        assert_eq!(parse_coordinates("NaN inf 1"), None);
+    }
+
+    #[test]
+    fn test_parse_pasted_coordinates() {
+        let pos = |x, y, z| WorldPos { x, y, z };
+        let text = "\
+            [12:00:00] [Render thread/INFO]: [CHAT] <Steve2> 3 portals at [100, 64, -200]\n\
+            The nearest minecraft:village is at [-232, ~, 104] (251 blocks away)\n\
+            no coordinates here\n\
+            <Alex> meet at the nether hub 10 70 20\n\
+            /execute in minecraft:the_end run tp @s 100.5 50.0 0.5 0.0 0.0\n\
+            /execute in minecraft:the_nether run tp @s 12.5 64.0 -30.5 0.0 0.0\n";
+        assert_eq!(
+            parse_pasted_coordinates(text, 70.0),
+            vec![
+                (None, pos(100.0, 64.0, -200.0)),
+                (None, pos(-232.0, 70.0, 104.0)),
+                (None, pos(10.0, 70.0, 20.0)),
+                (Some(Nether), pos(12.5, 64.0, -30.5)),
+            ],
+        );
+        assert_eq!(parse_pasted_coordinates("[~, ~, ~]", 70.0), vec![]);
+    }
+
+    #[test]
+    fn test_parse_dimension() {
+        assert_eq!(
+            parse_dimension("/execute in minecraft:the_nether run tp @s 1 2 3"),
+            Some(Nether),
+        );
+        assert_eq!(
+            parse_dimension("/execute in minecraft:overworld run tp @s 1 2 3"),
+            Some(Overworld),
+        );
+        assert_eq!(parse_dimension("Nether hub: 1 2 3"), None);
+        assert_eq!(parse_dimension("minecraft:the_end"), None);
+    }
+
+    #[test]
+    fn test_mentions_unsupported_dimension() {
+        assert!(mentions_unsupported_dimension(
+            "/execute in minecraft:the_end run tp @s 1 2 3"
+        ));
+        assert!(mentions_unsupported_dimension(
+            "/execute in twilightforest:twilight_forest run tp @s 1 2 3"
+        ));
+        assert!(!mentions_unsupported_dimension(
+            "/execute in minecraft:the_nether run tp @s 1 2 3"
+        ));
+        assert!(!mentions_unsupported_dimension("1 2 3"));
+    }
+}
@@ -1,9 +1,13 @@
+use std::fmt;
+
 use egui::NumExt;
 use serde::{Deserialize, Serialize};
 
 use crate::util::max_range_distance_to;
+use crate::world::{DEFAULT_LINK_SEARCH_STEP_LIMIT, PortalDestinations};
 use crate::{
-    Axis, BlockPos, BlockRegion, ConvertDimension, Dimension, Entity, PortalId, WorldRegion,
+    Axis, BlockPos, BlockRegion, ConvertDimension, Dimension, Entity, PortalId, WorldPortals,
+    WorldRegion,
 };
 
 /// Horizontal axis perpendicular to a portal's surface.
@@ -32,6 +36,112 @@ impl PortalAxis {
             PortalAxis::Z => PortalAxis::X,
         }
     }
+
+    /// Returns a human-friendly description of the directions from which the
+    /// portal is entered.
+    pub fn entry_directions(self) -> &'static str {
+        match self {
+            PortalAxis::X => "east/west",
+            PortalAxis::Z => "north/south",
+        }
+    }
+}
+
+/// Assumption about the terrain where Minecraft generates a new portal.
+///
+/// Minecraft first looks for existing space near the arrival position, and
+/// only builds an obsidian platform if there is none. The terrain is not
+/// modeled, so the outcome has to be assumed.
+#[derive(Serialize, Deserialize, Debug, Default, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum NewPortalPlacement {
+    /// There is enough space at the arrival position, so the new portal is
+    /// generated there.
+    #[default]
+    ExistingAir,
+    /// There is no suitable space nearby, so the new portal is generated on an
+    /// obsidian platform at the arrival position, with its height clamped.
+    Platform,
+}
+
+/// Reason that an entity won't fit in a portal.
+#[derive(Serialize, Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum EntityWontFit {
+    /// The entity is wider than the inside of the portal.
+    TooWide {
+        /// Width of the portal, in blocks.
+        portal_width: i64,
+    },
+    /// The entity is taller than the inside of the portal.
+    TooTall {
+        /// Height of the portal, in blocks.
+        portal_height: i64,
+    },
+}
+
+impl fmt::Display for EntityWontFit {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            EntityWontFit::TooWide { portal_width } => {
+                write!(f, "Entity too wide for {portal_width}-block-wide portal")
+            }
+            EntityWontFit::TooTall { portal_height } => {
+                write!(f, "Entity too tall for {portal_height}-block-tall portal")
+            }
+        }
+    }
+}
+
+/// Reason that two corners don't describe a valid portal.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum InvalidCorners {
+    /// The corners differ along both the X and Z axes, so the portal isn't
+    /// flat.
+    NotFlat,
+    /// The portal is narrower than [`Portal::MIN_WIDTH`].
+    TooNarrow {
+        /// Width of the portal, in blocks.
+        width: i64,
+    },
+    /// The portal is shorter than [`Portal::MIN_HEIGHT`].
+    TooShort {
+        /// Height of the portal, in blocks.
+        height: i64,
+    },
+    /// The portal or its frame extends beyond the height limits of the
+    /// dimension.
+    OutOfBounds {
+        /// Lowest Y coordinate that the portal blocks can be at.
+        lowest_y: i64,
+        /// Highest Y coordinate that the portal blocks can be at.
+        highest_y: i64,
+    },
+}
+
+impl fmt::Display for InvalidCorners {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            InvalidCorners::NotFlat => {
+                write!(f, "Corners must have the same X or Z coordinate")
+            }
+            InvalidCorners::TooNarrow { width } => write!(
+                f,
+                "{width}-block-wide portal is narrower than {}",
+                Portal::MIN_WIDTH,
+            ),
+            InvalidCorners::TooShort { height } => write!(
+                f,
+                "{height}-block-tall portal is shorter than {}",
+                Portal::MIN_HEIGHT,
+            ),
+            InvalidCorners::OutOfBounds {
+                lowest_y,
+                highest_y,
+            } => write!(
+                f,
+                "Portal must be between Y={lowest_y} and Y={highest_y} to leave room for the frame",
+            ),
+        }
+    }
 }
 
 /// Portal in an an unspecified dimension.
@@ -40,6 +150,10 @@ pub struct Portal {
     /// Unique ID for the portal.
     #[serde(skip, default = "PortalId::new")]
     pub id: PortalId,
+    /// ID of the portal in the file it was loaded from or saved to, which is
+    /// kept across saving and loading. See [`crate::World::to_json()`].
+    #[serde(skip)]
+    pub file_id: Option<u64>,
     /// Human-friendly name of the portal.
     #[serde(default)]
     pub name: String,
@@ -50,6 +164,9 @@ pub struct Portal {
     pub region: BlockRegion,
     /// Portal axis (opposite from what the game says).
     pub axis: PortalAxis,
+    /// Whether the portal has been built in-game.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub built: bool,
 }
 
 impl Portal {
@@ -70,11 +187,57 @@ impl Portal {
     ///
     /// Returns `None` if the entity won't fit in the portal.
     pub fn entity_collision_region(&self, entity: Entity) -> Option<WorldRegion> {
+        self.try_entity_collision_region(entity).ok()
+    }
+
+    /// Returns whether `entity` fits in the portal and can use it.
+    pub fn accepts(&self, entity: Entity) -> bool {
+        self.entity_collision_region(entity).is_some()
+    }
+
+    /// Returns where `entity` arrives when it enters the portal in `dimension`,
+    /// among `portals`, or the reason that the entity won't fit in the portal.
+    pub fn links_to<'a>(
+        &self,
+        portals: &'a WorldPortals,
+        dimension: Dimension,
+        entity: Entity,
+    ) -> Result<PortalDestinations<'a>, EntityWontFit> {
+        self.links_to_with_step_limit(portals, dimension, entity, DEFAULT_LINK_SEARCH_STEP_LIMIT)
+    }
+
+    /// Same as [`Portal::links_to()`], but with a custom `step_limit` for
+    /// [`WorldPortals::portal_destinations_with_step_limit()`].
+    pub fn links_to_with_step_limit<'a>(
+        &self,
+        portals: &'a WorldPortals,
+        dimension: Dimension,
+        entity: Entity,
+        step_limit: usize,
+    ) -> Result<PortalDestinations<'a>, EntityWontFit> {
+        let destination_dimension = dimension.other();
+        let destination_region = self
+            .try_entity_collision_region(entity)?
+            .convert_dimension(dimension, destination_dimension);
+        Ok(portals.portal_destinations_with_step_limit(
+            destination_dimension,
+            destination_region.block_region_containing(),
+            step_limit,
+        ))
+    }
+
+    /// Returns the region where an entity can collide with the portal and thus
+    /// be teleported using it, or the reason that the entity won't fit in the
+    /// portal.
+    pub fn try_entity_collision_region(
+        &self,
+        entity: Entity,
+    ) -> Result<WorldRegion, EntityWontFit> {
         let mut result = WorldRegion::from(self.region);
-        result.min.x -= entity.width / 2.0;
-        result.min.z -= entity.width / 2.0;
-        result.max.x += entity.width / 2.0;
-        result.max.z += entity.width / 2.0;
+        result.min.x -= entity.half_width();
+        result.min.z -= entity.half_width();
+        result.max.x += entity.half_width();
+        result.max.z += entity.half_width();
         if entity.is_projectile {
             result.min.y -= entity.height;
         }
@@ -84,7 +247,17 @@ impl Portal {
             result.max[self.width_axis()] -= entity.width;
             result.max.y -= entity.height;
         }
-        result.is_valid().then_some(result)
+        if result.min[self.width_axis()] > result.max[self.width_axis()] {
+            Err(EntityWontFit::TooWide {
+                portal_width: self.width(),
+            })
+        } else if result.min.y > result.max.y {
+            Err(EntityWontFit::TooTall {
+                portal_height: self.height(),
+            })
+        } else {
+            Ok(result)
+        }
     }
 
     /// Returns the region where an entity may try to arrive.
@@ -102,10 +275,49 @@ impl Portal {
         )
     }
 
+    /// Returns the block at the center of the region where an entity may try
+    /// to arrive. `destination_dimension` is the dimension the portal leads
+    /// to, _not_ the one it is in.
+    ///
+    /// This is useful for precisely placing ender pearl stasis chambers.
+    pub fn destination_block(
+        &self,
+        entity: Entity,
+        destination_dimension: Dimension,
+    ) -> Option<BlockPos> {
+        let center = self
+            .entity_collision_region(entity)?
+            .convert_dimension(destination_dimension.other(), destination_dimension)
+            .center();
+        Some(center.into())
+    }
+
+    /// Returns the block where a new portal is predicted to be generated if an
+    /// entity enters this portal and no existing portal is in range.
+    /// `destination_dimension` is the dimension the portal leads to, _not_ the
+    /// one it is in.
+    pub fn new_portal_block(
+        &self,
+        entity: Entity,
+        destination_dimension: Dimension,
+        placement: NewPortalPlacement,
+    ) -> Option<BlockPos> {
+        let mut pos = self.destination_block(entity, destination_dimension)?;
+        match placement {
+            NewPortalPlacement::ExistingAir => (),
+            NewPortalPlacement::Platform => {
+                let y_range = destination_dimension.new_portal_platform_y_range();
+                pos.y = pos.y.clamp(*y_range.start(), *y_range.end());
+            }
+        }
+        Some(pos)
+    }
+
     /// Constructs a new portal at `pos` of the smallest possible size.
     pub fn new_minimal(pos: BlockPos, axis: PortalAxis, dimension: Dimension) -> Self {
         Self {
             id: PortalId::new(),
+            file_id: None,
             name: String::new(),
             color: [127, 127, 127],
             region: BlockRegion {
@@ -121,7 +333,32 @@ impl Portal {
                 },
             },
             axis,
+            built: false,
+        }
+    }
+
+    /// Constructs a portal of the smallest possible size in the other dimension,
+    /// at the position corresponding to the center of this one. The new portal
+    /// has the same axis and color, and its name has the dimension appended.
+    pub fn new_linked_pair(&self, dimension: Dimension) -> Self {
+        let other_dimension = dimension.other();
+        let center = WorldRegion::from(self.region)
+            .center()
+            .convert_dimension(dimension, other_dimension);
+        let pos = BlockPos {
+            y: self
+                .region
+                .min
+                .y
+                .clamp(other_dimension.y_min(), other_dimension.y_max()),
+            ..center.into()
+        };
+        let mut ret = Self::new_minimal(pos, self.axis, other_dimension);
+        ret.color = self.color;
+        if !self.name.is_empty() {
+            ret.name = format!("{} ({other_dimension})", self.name);
         }
+        ret
     }
 
     /// Constructs a portal from a region for testing. The axis is inferred from
@@ -139,10 +376,12 @@ impl Portal {
         };
         Self {
             id,
+            file_id: None,
             name: id.to_string(),
             color: [0; 3],
             region,
             axis,
+            built: false,
         }
     }
 
@@ -155,6 +394,90 @@ impl Portal {
         self.axis.into()
     }
 
+    /// Returns the width of the portal, not including the frame.
+    pub fn width(&self) -> i64 {
+        let w = self.width_axis();
+        self.region.max[w] - self.region.min[w] + 1
+    }
+    /// Returns the height of the portal, not including the frame.
+    pub fn height(&self) -> i64 {
+        self.region.max.y - self.region.min.y + 1
+    }
+
+    /// Returns the region and axis of a portal in `dimension` with opposite
+    /// corners `a` and `b`, which may be given in any order.
+    pub fn region_from_corners(
+        a: BlockPos,
+        b: BlockPos,
+        dimension: Dimension,
+    ) -> Result<(BlockRegion, PortalAxis), InvalidCorners> {
+        let region = BlockRegion {
+            min: BlockPos {
+                x: a.x.min(b.x),
+                y: a.y.min(b.y),
+                z: a.z.min(b.z),
+            },
+            max: BlockPos {
+                x: a.x.max(b.x),
+                y: a.y.max(b.y),
+                z: a.z.max(b.z),
+            },
+        };
+        let axis = if a.x == b.x {
+            PortalAxis::X
+        } else if a.z == b.z {
+            PortalAxis::Z
+        } else {
+            return Err(InvalidCorners::NotFlat);
+        };
+        let w = Axis::from(axis.other());
+        let width = region.max[w] - region.min[w] + 1;
+        let height = region.max.y - region.min.y + 1;
+        // Leave room for the obsidian frame
+        let lowest_y = dimension.y_min() + 1;
+        let highest_y = dimension.y_max() - 1;
+        if width < Self::MIN_WIDTH {
+            Err(InvalidCorners::TooNarrow { width })
+        } else if height < Self::MIN_HEIGHT {
+            Err(InvalidCorners::TooShort { height })
+        } else if region.min.y < lowest_y || region.max.y > highest_y {
+            Err(InvalidCorners::OutOfBounds {
+                lowest_y,
+                highest_y,
+            })
+        } else {
+            Ok((region, axis))
+        }
+    }
+
+    /// Moves and resizes the portal to fill the region between opposite
+    /// corners `a` and `b`, changing its axis if necessary.
+    pub fn set_corners(
+        &mut self,
+        a: BlockPos,
+        b: BlockPos,
+        dimension: Dimension,
+    ) -> Result<(), InvalidCorners> {
+        let (region, axis) = Self::region_from_corners(a, b, dimension)?;
+        self.region = region;
+        self.axis = axis;
+        Ok(())
+    }
+
+    /// Returns the number of obsidian blocks in the portal's frame, not counting
+    /// the corners, which are optional.
+    pub fn obsidian_count(&self) -> i64 {
+        2 * (self.width() + self.height())
+    }
+
+    /// Returns the distance from the origin to the center of the portal along
+    /// the X or Z axis, whichever is farther. This is how the distance to the
+    /// world border is measured.
+    pub fn distance_from_origin(&self) -> f64 {
+        let center = WorldRegion::from(self.region).center();
+        center.x.abs().max(center.z.abs())
+    }
+
     /// Returns a nonempty human-friendly name for the portal.
     pub fn display_name(&self) -> &str {
         if self.name.is_empty() {
@@ -248,7 +571,7 @@ impl Portal {
     /// that the portal is valid. `min` is preserved.
     pub fn adjust_width<R>(&mut self, f: impl FnOnce(&mut i64) -> R) -> R {
         let w = self.width_axis();
-        let mut width = self.region.max[w] - self.region.min[w] + 1;
+        let mut width = self.width();
         let r = f(&mut width);
         width = width.at_least(Self::MIN_WIDTH);
         self.region.max[w] = self.region.min[w].saturating_add(width - 1);
@@ -261,7 +584,7 @@ impl Portal {
         // Bedrock can be broken in survival, but we can't use the full height
         // of the dimension because we need to leave room for the obsidian
         // frame.
-        let mut height = self.region.max.y - self.region.min.y + 1;
+        let mut height = self.height();
         let r = f(&mut height);
         height = height.at_least(Self::MIN_HEIGHT);
         self.region.max.y = self.region.min.y.saturating_add(height - 1);
@@ -298,6 +621,28 @@ impl Portal {
         r
     }
 
+    /// Moves the portal by `offset`, keeping it within the build limits of
+    /// `dimension`.
+    pub fn translate(&mut self, offset: BlockPos, dimension: Dimension) {
+        self.adjust_min(|min| *min += offset, true, dimension);
+    }
+
+    /// Rotates the portal 90 degrees around the vertical line through the
+    /// center of `pivot`. Positive rotation is clockwise as seen from above.
+    pub fn rotate(&mut self, pivot: BlockPos, clockwise: bool) {
+        self.region = if clockwise {
+            self.region.rotated_clockwise(pivot)
+        } else {
+            self.region.rotated_counterclockwise(pivot)
+        };
+        self.axis = self.axis.other();
+    }
+
+    /// Mirrors the portal along `axis` across the center of `pivot`.
+    pub fn mirror(&mut self, axis: Axis, pivot: BlockPos) {
+        self.region = self.region.mirrored(axis, pivot);
+    }
+
     /// Returns whether `self` is within the portal search range for `pos`.
     pub fn is_in_range_of_point(&self, pos: BlockPos, dimension: Dimension) -> bool {
         // Ignore Y axis
@@ -331,3 +676,255 @@ impl Portal {
             ) <= r
     }
 }
+
+/// Commonly-used portal configuration that can be placed with one click.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct PortalTemplate {
+    /// Name of the template.
+    pub name: String,
+    /// Name given to new portals, in which `{n}` is replaced by the number of
+    /// the new portal in its dimension.
+    pub portal_name: String,
+    /// Color of new portals.
+    pub color: [u8; 3],
+    /// Width of new portals, not counting the frame.
+    pub width: i64,
+    /// Height of new portals, not counting the frame.
+    pub height: i64,
+    /// Axis of new portals.
+    pub axis: PortalAxis,
+}
+
+impl PortalTemplate {
+    /// Returns the templates available by default.
+    pub fn defaults() -> Vec<Self> {
+        vec![
+            PortalTemplate {
+                name: "Standard 2×3".to_owned(),
+                portal_name: String::new(),
+                color: [127, 127, 127],
+                width: 2,
+                height: 3,
+                axis: PortalAxis::X,
+            },
+            PortalTemplate {
+                name: "Ghast 5×5".to_owned(),
+                portal_name: "Ghast {n}".to_owned(),
+                color: [255, 255, 255],
+                width: 5,
+                height: 5,
+                axis: PortalAxis::X,
+            },
+        ]
+    }
+
+    /// Constructs a template with the same size, axis, color, and name as
+    /// `portal`.
+    pub fn from_portal(portal: &Portal) -> Self {
+        PortalTemplate {
+            name: format!(
+                "{} ({}×{})",
+                portal.display_name(),
+                portal.width(),
+                portal.height()
+            ),
+            portal_name: portal.name.clone(),
+            color: portal.color,
+            width: portal.width(),
+            height: portal.height(),
+            axis: portal.axis,
+        }
+    }
+
+    /// Constructs a new portal from the template with its minimum corner at
+    /// `pos`. `n` is the number substituted into the portal name.
+    pub fn instantiate(&self, pos: BlockPos, dimension: Dimension, n: usize) -> Portal {
+        let mut portal = Portal::new_minimal(pos, self.axis, dimension);
+        portal.name = self.portal_name.replace("{n}", &n.to_string());
+        portal.color = self.color;
+        portal.adjust_width(|w| *w = self.width);
+        portal.adjust_height(|h| *h = self.height, dimension);
+        portal
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_portal_template() {
+        let template = &PortalTemplate::defaults()[1];
+        let portal = template.instantiate(BlockPos::from([10, 64, 0]), Dimension::Nether, 3);
+        assert_eq!(portal.name, "Ghast 3");
+        assert_eq!(portal.region, BlockRegion::from(([10, 64, 0], [10, 68, 4])));
+        assert_eq!(PortalTemplate::from_portal(&portal).width, 5);
+
+        // Templates never make invalid portals.
+        let template = PortalTemplate {
+            width: 0,
+            height: 1000,
+            ..template.clone()
+        };
+        let portal = template.instantiate(BlockPos::from([10, 64, 0]), Dimension::Nether, 3);
+        assert_eq!(portal.width(), Portal::MIN_WIDTH);
+        assert!(portal.region.max.y < Dimension::Nether.y_max());
+    }
+
+    #[test]
+    fn test_ender_pearl_destination_block() {
+        let portal = Portal::new_test(([10, 64, 0], [10, 66, 1]));
+        assert_eq!(
+            portal.destination_block(Entity::ENDER_PEARL, Dimension::Overworld),
+            Some(BlockPos::from([84, 65, 8])),
+        );
+        assert_eq!(
+            portal.destination_block(Entity::ENDER_PEARL, Dimension::Nether),
+            Some(BlockPos::from([1, 65, 0])),
+        );
+    }
+
+    #[test]
+    fn test_new_portal_block() {
+        let portal = Portal::new_test(([10, 20, 0], [10, 22, 1]));
+        let entity = Entity::PLAYER;
+        assert_eq!(
+            portal.new_portal_block(
+                entity,
+                Dimension::Overworld,
+                NewPortalPlacement::ExistingAir
+            ),
+            Some(BlockPos::from([84, 20, 7])),
+        );
+        assert_eq!(
+            portal.new_portal_block(entity, Dimension::Overworld, NewPortalPlacement::Platform),
+            Some(BlockPos::from([84, 70, 7])),
+        );
+    }
+
+    #[test]
+    fn test_entity_wont_fit() {
+        let portal = Portal::new_test(([10, 64, 0], [10, 66, 1])); // 2×3
+        assert!(portal.try_entity_collision_region(Entity::PLAYER).is_ok());
+        assert_eq!(
+            portal.try_entity_collision_region(Entity::GHAST),
+            Err(EntityWontFit::TooWide { portal_width: 2 }),
+        );
+        let tall = Entity {
+            height: 3.5,
+            ..Entity::PLAYER
+        };
+        assert_eq!(
+            portal.try_entity_collision_region(tall),
+            Err(EntityWontFit::TooTall { portal_height: 3 }),
+        );
+        let tall_projectile = Entity {
+            is_projectile: true,
+            ..tall
+        };
+        assert!(portal.try_entity_collision_region(tall_projectile).is_ok());
+    }
+
+    #[test]
+    fn test_accepts_and_links_to() {
+        let portals = WorldPortals {
+            overworld: vec![Portal::new_test(([80, 64, 0], [80, 66, 1]))],
+            nether: vec![
+                Portal::new_test(([10, 64, 0], [10, 66, 1])),
+                Portal::new_test(([1000, 64, 0], [1000, 66, 1])),
+            ],
+        };
+        let portal = &portals.overworld[0];
+        assert!(portal.accepts(Entity::PLAYER));
+        assert!(!portal.accepts(Entity::GHAST));
+
+        let destinations = portal
+            .links_to(&portals, Dimension::Overworld, Entity::PLAYER)
+            .expect("player should fit");
+        assert_eq!(destinations.existing_portals, vec![&portals.nether[0]]);
+        assert!(!destinations.new_portal);
+        assert_eq!(
+            portal.links_to(&portals, Dimension::Overworld, Entity::GHAST),
+            Err(EntityWontFit::TooWide { portal_width: 2 }),
+        );
+    }
+
+    #[test]
+    fn test_set_corners() {
+        let mut portal = Portal::new_test(([0, 64, 0], [0, 66, 1]));
+        assert_eq!(
+            portal.set_corners([13, 70, 5].into(), [10, 74, 5].into(), Dimension::Overworld),
+            Ok(()),
+        );
+        assert_eq!(portal.region, BlockRegion::from(([10, 70, 5], [13, 74, 5])));
+        assert_eq!(portal.axis, PortalAxis::Z);
+
+        let old_portal = portal.clone();
+        let mut check_err = |a: [i64; 3], b: [i64; 3], expected| {
+            let result = portal.set_corners(a.into(), b.into(), Dimension::Overworld);
+            assert_eq!(result, Err(expected));
+            assert_eq!(portal.region, old_portal.region);
+        };
+        check_err([0, 64, 0], [1, 66, 1], InvalidCorners::NotFlat);
+        check_err(
+            [0, 64, 0],
+            [0, 66, 0],
+            InvalidCorners::TooNarrow { width: 1 },
+        );
+        check_err(
+            [0, 64, 0],
+            [0, 65, 5],
+            InvalidCorners::TooShort { height: 2 },
+        );
+        check_err(
+            [0, 300, 0],
+            [0, 319, 5],
+            InvalidCorners::OutOfBounds {
+                lowest_y: -63,
+                highest_y: 318,
+            },
+        );
+
+        // A tall portal can be moved right up to the height limit.
+        let mut portal = Portal::new_test(([0, 64, 0], [0, 94, 1]));
+        assert_eq!(
+            portal.set_corners([0, 314, 0].into(), [0, 318, 1].into(), Dimension::Overworld),
+            Ok(()),
+        );
+        assert_eq!(portal.region, BlockRegion::from(([0, 314, 0], [0, 318, 1])));
+        assert_eq!(
+            portal.set_corners([0, 250, 0].into(), [0, 254, 1].into(), Dimension::Nether),
+            Ok(()),
+        );
+        assert_eq!(portal.region, BlockRegion::from(([0, 250, 0], [0, 254, 1])));
+    }
+
+    #[test]
+    fn test_obsidian_count() {
+        assert_eq!(
+            Portal::new_test(([0, 64, 0], [0, 66, 1])).obsidian_count(),
+            10
+        );
+        assert_eq!(
+            Portal::new_test(([0, 64, 0], [3, 68, 0])).obsidian_count(),
+            18
+        );
+    }
+
+    #[test]
+    fn test_distance_from_origin() {
+        let portal = Portal::new_test(([-1000, 64, 20], [-1000, 66, 21]));
+        assert_eq!(portal.distance_from_origin(), 999.5);
+        let portal = Portal::new_test(([5, 64, 3000], [6, 66, 3000]));
+        assert_eq!(portal.distance_from_origin(), 3000.5);
+    }
+
+    #[test]
+    fn test_point_entity_collision_region() {
+        let portal = Portal::new_test(([10, 64, 0], [10, 66, 1]));
+        assert_eq!(
+            portal.entity_collision_region(Entity::POINT),
+            Some(WorldRegion::from(portal.region)),
+        );
+    }
+}
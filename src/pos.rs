@@ -1,5 +1,5 @@
 use std::fmt;
-use std::ops::{Index, IndexMut};
+use std::ops::{Add, AddAssign, Index, IndexMut, Sub};
 
 use serde::{Deserialize, Serialize};
 
@@ -68,6 +68,16 @@ impl From<WorldPos> for BlockPos {
         }
     }
 }
+impl fmt::Display for BlockPos {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.x.fmt(f)?;
+        write!(f, ", ")?;
+        self.y.fmt(f)?;
+        write!(f, ", ")?;
+        self.z.fmt(f)?;
+        Ok(())
+    }
+}
 impl From<[i64; 3]> for BlockPos {
     fn from([x, y, z]: [i64; 3]) -> Self {
         BlockPos { x, y, z }
@@ -79,6 +89,33 @@ impl From<BlockPos> for [i64; 3] {
         [x, y, z]
     }
 }
+impl Add for BlockPos {
+    type Output = Self;
+
+    fn add(self, rhs: Self) -> Self::Output {
+        BlockPos {
+            x: self.x + rhs.x,
+            y: self.y + rhs.y,
+            z: self.z + rhs.z,
+        }
+    }
+}
+impl AddAssign for BlockPos {
+    fn add_assign(&mut self, rhs: Self) {
+        *self = *self + rhs;
+    }
+}
+impl Sub for BlockPos {
+    type Output = Self;
+
+    fn sub(self, rhs: Self) -> Self::Output {
+        BlockPos {
+            x: self.x - rhs.x,
+            y: self.y - rhs.y,
+            z: self.z - rhs.z,
+        }
+    }
+}
 impl BlockPos {
     /// Returns the squared Euclidean distance between `self` and `other`.
     pub fn euclidean_distance_sq(&self, other: &Self) -> i64 {
@@ -129,6 +166,51 @@ impl From<BlockPos> for WorldPos {
         }
     }
 }
+impl Add for WorldPos {
+    type Output = Self;
+
+    fn add(self, rhs: Self) -> Self::Output {
+        WorldPos {
+            x: self.x + rhs.x,
+            y: self.y + rhs.y,
+            z: self.z + rhs.z,
+        }
+    }
+}
+impl AddAssign for WorldPos {
+    fn add_assign(&mut self, rhs: Self) {
+        *self = *self + rhs;
+    }
+}
+impl Sub for WorldPos {
+    type Output = Self;
+
+    fn sub(self, rhs: Self) -> Self::Output {
+        WorldPos {
+            x: self.x - rhs.x,
+            y: self.y - rhs.y,
+            z: self.z - rhs.z,
+        }
+    }
+}
+impl WorldPos {
+    /// Returns the nearest block coordinates, rounding each axis separately.
+    pub fn round(self) -> BlockPos {
+        BlockPos {
+            x: self.x.round() as i64,
+            y: self.y.round() as i64,
+            z: self.z.round() as i64,
+        }
+    }
+    /// Returns the coordinates of the block containing the position.
+    pub fn floor(self) -> BlockPos {
+        BlockPos {
+            x: self.x.floor() as i64,
+            y: self.y.floor() as i64,
+            z: self.z.floor() as i64,
+        }
+    }
+}
 impl ConvertDimension for WorldPos {
     fn nether_to_overworld(self) -> Self {
         WorldPos {
@@ -0,0 +1,192 @@
+//! Crash recovery files, written independently of the user's own save file.
+
+use std::fs::File;
+use std::path::{Path, PathBuf};
+
+use crate::World;
+
+const PREFIX: &str = "recovery-";
+const EXTENSION: &str = "json";
+const LOCK_EXTENSION: &str = "lock";
+
+/// Recovery file for the current session.
+///
+/// The file is written whenever there are unsaved changes and deleted when the
+/// changes are saved or discarded, so a leftover file means that a previous
+/// session did not exit cleanly.
+///
+/// While the file exists, the session holds a lock on a matching lock file so
+/// that other sessions running at the same time leave it alone. The operating
+/// system releases the lock if the session crashes.
+#[derive(Debug)]
+pub struct RecoveryFile {
+    path: PathBuf,
+    /// World and file path most recently written to the file, or `None` if
+    /// the file does not exist.
+    contents: Option<(World, Option<PathBuf>)>,
+    /// Locked lock file, which is held while the recovery file exists.
+    lock: Option<File>,
+}
+
+impl Default for RecoveryFile {
+    fn default() -> Self {
+        let timestamp = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs();
+        let pid = std::process::id();
+        Self {
+            path: recovery_dir().join(format!("{PREFIX}{timestamp:012}-{pid}.{EXTENSION}")),
+            contents: None,
+            lock: None,
+        }
+    }
+}
+
+impl RecoveryFile {
+    /// Writes `world` and the path of the file it belongs to to the recovery
+    /// file if either has changed since the last write.
+    pub fn write(&mut self, world: &World, file_path: Option<&Path>) {
+        if self
+            .contents
+            .as_ref()
+            .is_some_and(|(w, p)| w == world && p.as_deref() == file_path)
+        {
+            return;
+        }
+        let result = (|| {
+            std::fs::create_dir_all(recovery_dir()).map_err(|e| e.to_string())?;
+            if self.lock.is_none() {
+                let lock = File::create(lock_path(&self.path)).map_err(|e| e.to_string())?;
+                lock.try_lock().map_err(|e| e.to_string())?;
+                self.lock = Some(lock);
+            }
+            let world: serde_json::Value = world
+                .to_json(true)
+                .and_then(|s| serde_json::from_str(&s))
+                .map_err(|e| e.to_string())?;
+            let s = serde_json::json!({ "file_path": file_path, "world": world }).to_string();
+            std::fs::write(&self.path, s).map_err(|e| e.to_string())
+        })();
+        match result {
+            Ok(()) => self.contents = Some((world.clone(), file_path.map(Path::to_path_buf))),
+            Err(e) => log::error!("error writing recovery file {:?}: {e}", self.path),
+        }
+    }
+
+    /// Deletes the recovery file, if it exists.
+    pub fn remove(&mut self) {
+        if self.contents.take().is_some()
+            && let Err(e) = std::fs::remove_file(&self.path)
+        {
+            log::error!("error removing recovery file {:?}: {e}", self.path);
+        }
+        if let Some(lock) = self.lock.take() {
+            drop(lock);
+            if let Err(e) = std::fs::remove_file(lock_path(&self.path)) {
+                log::error!("error removing recovery lock file {:?}: {e}", self.path);
+            }
+        }
+    }
+}
+
+/// Returns the path of the lock file for the recovery file at `path`.
+fn lock_path(path: &Path) -> PathBuf {
+    path.with_extension(LOCK_EXTENSION)
+}
+
+/// Returns the directory in which recovery files are stored.
+fn recovery_dir() -> PathBuf {
+    std::env::temp_dir().join(crate::APP_ID)
+}
+
+/// Returns the world and file path from the most recent recovery file left
+/// over from a previous session, and deletes all leftover recovery files.
+///
+/// Recovery files that belong to a session that is still running are left
+/// alone. Recovery files that cannot be read are skipped.
+pub fn take_previous() -> Option<(World, Option<PathBuf>)> {
+    let mut paths = std::fs::read_dir(recovery_dir())
+        .ok()?
+        .filter_map(|entry| Some(entry.ok()?.path()))
+        .filter(|path| {
+            path.extension().is_some_and(|ext| ext == EXTENSION)
+                && path
+                    .file_name()
+                    .and_then(|name| name.to_str())
+                    .is_some_and(|name| name.starts_with(PREFIX))
+        })
+        .collect::<Vec<_>>();
+    paths.sort(); // timestamps are zero-padded, so the newest file is last
+
+    // Claim the files of sessions that are no longer running. The locks are
+    // held until the files are deleted.
+    let claimed = paths
+        .into_iter()
+        .filter_map(|path| {
+            let lock = File::options()
+                .write(true)
+                .create(true)
+                .truncate(false)
+                .open(lock_path(&path))
+                .ok()?;
+            lock.try_lock().ok()?;
+            Some((path, lock))
+        })
+        .collect::<Vec<_>>();
+
+    let previous = claimed.iter().rev().find_map(|(path, _)| {
+        let contents = std::fs::read(path).ok()?;
+        parse_recovery_file(&String::from_utf8_lossy(&contents))
+            .inspect_err(|e| log::warn!("error reading recovery file {path:?}: {e}"))
+            .ok()
+    });
+
+    for (path, lock) in claimed {
+        if let Err(e) = std::fs::remove_file(&path) {
+            log::error!("error removing recovery file {path:?}: {e}");
+        }
+        drop(lock);
+        if let Err(e) = std::fs::remove_file(lock_path(&path)) {
+            log::error!("error removing recovery lock file {path:?}: {e}");
+        }
+    }
+
+    previous
+}
+
+/// Parses the contents of a recovery file.
+///
+/// Recovery files written by older versions contain only the world.
+fn parse_recovery_file(s: &str) -> serde_json::Result<(World, Option<PathBuf>)> {
+    let value: serde_json::Value = serde_json::from_str(s)?;
+    match value.get("world") {
+        Some(world) => Ok((
+            World::from_json(&world.to_string())?,
+            serde_json::from_value(value["file_path"].clone())?,
+        )),
+        None => Ok((World::from_json(s)?, None)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_recovery_file() {
+        let mut world = World::default();
+        world.test_points.overworld.push(crate::WorldPos::default());
+        let world_json = world.to_json(true).expect("serialization failed");
+
+        let old_format = parse_recovery_file(&world_json).expect("deserialization failed");
+        assert_eq!(old_format, (world.clone(), None));
+
+        let s = format!(r#"{{"file_path": "worlds/example.json", "world": {world_json}}}"#);
+        let new_format = parse_recovery_file(&s).expect("deserialization failed");
+        assert_eq!(
+            new_format,
+            (world, Some(PathBuf::from("worlds/example.json")))
+        );
+    }
+}
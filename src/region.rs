@@ -146,6 +146,52 @@ impl BlockRegion {
         self.max[axis] -= 1;
         self.split_at(axis, coordinate)
     }
+    /// Returns the region mirrored along `axis` across the center of `pivot`.
+    #[must_use]
+    pub fn mirrored(self, axis: Axis, pivot: BlockPos) -> BlockRegion {
+        let mut ret = self;
+        ret.min[axis] = 2 * pivot[axis] - self.max[axis];
+        ret.max[axis] = 2 * pivot[axis] - self.min[axis];
+        ret
+    }
+    /// Returns the region rotated 90 degrees clockwise (as seen from above,
+    /// with north up) around the vertical line through the center of `pivot`.
+    #[must_use]
+    pub fn rotated_clockwise(self, pivot: BlockPos) -> BlockRegion {
+        let BlockRegion { min, max } = self;
+        BlockRegion {
+            min: BlockPos {
+                x: pivot.x + pivot.z - max.z,
+                y: min.y,
+                z: pivot.z - pivot.x + min.x,
+            },
+            max: BlockPos {
+                x: pivot.x + pivot.z - min.z,
+                y: max.y,
+                z: pivot.z - pivot.x + max.x,
+            },
+        }
+    }
+    /// Returns the region rotated 90 degrees counterclockwise (as seen from
+    /// above, with north up) around the vertical line through the center of
+    /// `pivot`.
+    #[must_use]
+    pub fn rotated_counterclockwise(self, pivot: BlockPos) -> BlockRegion {
+        let BlockRegion { min, max } = self;
+        BlockRegion {
+            min: BlockPos {
+                x: pivot.x - pivot.z + min.z,
+                y: min.y,
+                z: pivot.z + pivot.x - max.x,
+            },
+            max: BlockPos {
+                x: pivot.x - pivot.z + max.z,
+                y: max.y,
+                z: pivot.z + pivot.x - min.x,
+            },
+        }
+    }
+
     /// Splits a region in half at `coordinate + 0.5` along `axis`.
     #[must_use]
     pub fn split_at(self, axis: Axis, coordinate: i64) -> [Option<BlockRegion>; 2] {
@@ -225,12 +271,37 @@ impl WorldRegion {
             && (self.min.y..=self.max.y).contains(&pos.y)
             && (self.min.z..=self.max.z).contains(&pos.z)
     }
+
+    /// Returns the smallest region containing both `self` and `other`.
+    pub fn union(self, other: Self) -> Self {
+        WorldRegion {
+            min: WorldPos {
+                x: self.min.x.min(other.min.x),
+                y: self.min.y.min(other.min.y),
+                z: self.min.z.min(other.min.z),
+            },
+            max: WorldPos {
+                x: self.max.x.max(other.max.x),
+                y: self.max.y.max(other.max.y),
+                z: self.max.z.max(other.max.z),
+            },
+        }
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_world_region_union() {
+        let a = WorldRegion::from(BlockRegion::from(([0, 64, -5], [1, 66, -5])));
+        let b = WorldRegion::from(BlockRegion::from(([-10, 70, 3], [-10, 72, 4])));
+        let expected = WorldRegion::from(BlockRegion::from(([-10, 64, -5], [1, 72, 4])));
+        assert_eq!(a.union(b), expected);
+        assert_eq!(b.union(a), expected);
+    }
+
     #[test]
     fn test_split_excluding_corners() {
         let min = BlockPos { x: 1, y: 2, z: 3 };
@@ -295,4 +366,26 @@ mod tests {
         block.max.z = 6;
         assert_eq!(block.split_excluding_corners(Axis::Z), [None, None],);
     }
+
+    #[test]
+    fn test_rotate_and_mirror() {
+        let region = BlockRegion::from(([3, 64, 10], [4, 66, 10]));
+        let pivot = BlockPos { x: 1, y: 0, z: 2 };
+
+        let cw = region.rotated_clockwise(pivot);
+        assert_eq!(cw, BlockRegion::from(([-7, 64, 4], [-7, 66, 5])));
+        assert_eq!(cw.rotated_counterclockwise(pivot), region);
+        assert_eq!(
+            (0..4).fold(region, |r, _| r.rotated_clockwise(pivot)),
+            region,
+        );
+
+        let mirrored = region.mirrored(Axis::X, pivot);
+        assert_eq!(mirrored, BlockRegion::from(([-2, 64, 10], [-1, 66, 10])));
+        assert_eq!(mirrored.mirrored(Axis::X, pivot), region);
+        assert_eq!(
+            region.mirrored(Axis::Z, pivot),
+            BlockRegion::from(([3, 64, -6], [4, 66, -6])),
+        );
+    }
 }
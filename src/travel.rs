@@ -0,0 +1,214 @@
+//! Travel distances between portals.
+
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+
+use crate::{Dimension, Portal, PortalId, PortalLinkResult, World, WorldRegion};
+
+/// Route between two portals in the same dimension by way of the other
+/// dimension.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Shortcut {
+    /// Portal at the end of the route.
+    pub destination: PortalId,
+    /// Horizontal distance saved compared to walking directly, in blocks.
+    pub blocks_saved: f64,
+    /// Horizontal distance traveled in the other dimension, in blocks.
+    pub blocks_traveled: f64,
+}
+
+impl Shortcut {
+    /// Returns a summary of the shortcut from the overworld, such as `Saves
+    /// 700 blocks (~2 min 5 s) of travel to Base`.
+    pub fn summary(&self, speed: TravelSpeed, destination_name: &str) -> String {
+        format!(
+            "Saves {:.0} blocks ({}) of travel to {destination_name}",
+            self.blocks_saved,
+            format_travel_time(speed.seconds(self.blocks_saved)),
+        )
+    }
+
+    /// Returns the travel time through the nether compared to the travel time
+    /// directly.
+    pub fn time_comparison(&self, speed: TravelSpeed) -> String {
+        let direct_blocks = self.blocks_saved + self.blocks_traveled;
+        format!(
+            "{} through the nether instead of {} directly ({})",
+            format_travel_time(speed.seconds(self.blocks_traveled)),
+            format_travel_time(speed.seconds(direct_blocks)),
+            speed.name(),
+        )
+    }
+}
+
+/// Mode of travel, used to estimate travel times.
+#[derive(Serialize, Deserialize, Debug, Default, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum TravelSpeed {
+    #[default]
+    Walking,
+    Sprinting,
+    SprintJumping,
+    BoatOnIce,
+    Elytra,
+}
+
+impl TravelSpeed {
+    /// All modes of travel, from slowest to fastest.
+    pub const ALL: [TravelSpeed; 5] = [
+        TravelSpeed::Walking,
+        TravelSpeed::Sprinting,
+        TravelSpeed::SprintJumping,
+        TravelSpeed::BoatOnIce,
+        TravelSpeed::Elytra,
+    ];
+
+    /// Returns a human-friendly name for the mode of travel.
+    pub fn name(self) -> &'static str {
+        match self {
+            TravelSpeed::Walking => "Walking",
+            TravelSpeed::Sprinting => "Sprinting",
+            TravelSpeed::SprintJumping => "Sprint-Jumping",
+            TravelSpeed::BoatOnIce => "Boat on Packed Ice",
+            TravelSpeed::Elytra => "Elytra with Rockets",
+        }
+    }
+
+    /// Returns the approximate horizontal speed, in blocks per second.
+    pub fn blocks_per_second(self) -> f64 {
+        match self {
+            TravelSpeed::Walking => 4.317,
+            TravelSpeed::Sprinting => 5.612,
+            TravelSpeed::SprintJumping => 7.127,
+            TravelSpeed::BoatOnIce => 40.0,
+            TravelSpeed::Elytra => 33.5,
+        }
+    }
+
+    /// Returns the approximate time to travel `blocks`, in seconds.
+    ///
+    /// Blocks are counted in the dimension being traveled through, so one block
+    /// in the nether covers 8 blocks in the overworld.
+    pub fn seconds(self, blocks: f64) -> f64 {
+        blocks / self.blocks_per_second()
+    }
+}
+
+/// Formats an approximate duration, such as `~45 seconds` or `~3 min 20 s`.
+pub fn format_travel_time(seconds: f64) -> String {
+    let seconds = seconds.round() as u64;
+    if seconds < 60 {
+        format!("~{seconds} seconds")
+    } else if seconds < 3600 {
+        format!("~{} min {} s", seconds / 60, seconds % 60)
+    } else {
+        format!("~{} h {} min", seconds / 3600, seconds / 60 % 60)
+    }
+}
+
+/// Returns the horizontal distance between the centers of two portals in the
+/// same dimension.
+pub fn horizontal_distance(a: &Portal, b: &Portal) -> f64 {
+    let a = WorldRegion::from(a.region).center();
+    let b = WorldRegion::from(b.region).center();
+    (a.x - b.x).hypot(a.z - b.z)
+}
+
+/// Returns the route from each portal in `dimension` through the other
+/// dimension that saves the most walking, compared to walking directly to the
+/// portal at the end of the route.
+///
+/// Portals with no route that saves any walking are omitted.
+pub fn best_shortcuts(
+    world: &World,
+    links: &HashMap<PortalId, (PortalLinkResult, Vec<PortalId>)>,
+    dimension: Dimension,
+) -> HashMap<PortalId, Shortcut> {
+    let other_portals_by_id: HashMap<PortalId, &Portal> = world.portals[dimension.other()]
+        .iter()
+        .map(|p| (p.id, p))
+        .collect();
+
+    let mut ret = HashMap::new();
+    for start in &world.portals[dimension] {
+        let Some((PortalLinkResult::Portals { ids, .. }, _)) = links.get(&start.id) else {
+            continue;
+        };
+        let Some(arrival) = ids.first().and_then(|id| other_portals_by_id.get(id)) else {
+            continue;
+        };
+        for end in &world.portals[dimension] {
+            if end.id == start.id {
+                continue;
+            }
+            let Some((_, incoming)) = links.get(&end.id) else {
+                continue;
+            };
+            for departure in incoming.iter().filter_map(|id| other_portals_by_id.get(id)) {
+                let blocks_traveled = horizontal_distance(arrival, departure);
+                let blocks_saved = horizontal_distance(start, end) - blocks_traveled;
+                if blocks_saved > 0.0
+                    && ret
+                        .get(&start.id)
+                        .is_none_or(|s: &Shortcut| blocks_saved > s.blocks_saved)
+                {
+                    ret.insert(
+                        start.id,
+                        Shortcut {
+                            destination: end.id,
+                            blocks_saved,
+                            blocks_traveled,
+                        },
+                    );
+                }
+            }
+        }
+    }
+    ret
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_travel_time_through_nether() {
+        let mut world = World::default();
+        world.portals.overworld = vec![
+            Portal::new_test(([0, 64, 0], [0, 66, 1])),
+            Portal::new_test(([800, 64, 0], [800, 66, 1])),
+        ];
+        world.portals.nether = vec![
+            Portal::new_test(([0, 64, 0], [0, 66, 1])),
+            Portal::new_test(([100, 64, 0], [100, 66, 1])),
+        ];
+        let links = world.analyze(crate::Entity::PLAYER).to_link_map();
+        let shortcuts = best_shortcuts(&world, &links, Dimension::Overworld);
+
+        let start = &world.portals.overworld[0];
+        let end = &world.portals.overworld[1];
+        let shortcut = shortcuts[&start.id];
+        assert_eq!(shortcut.destination, end.id);
+        assert_eq!(shortcut.blocks_traveled, 100.0);
+        assert_eq!(shortcut.blocks_saved, 700.0);
+        // The return trip saves the same distance.
+        assert_eq!(shortcuts[&end.id].destination, start.id);
+
+        let speed = TravelSpeed::Sprinting;
+        assert_eq!(
+            shortcut.summary(speed, "Base"),
+            "Saves 700 blocks (~2 min 5 s) of travel to Base",
+        );
+        assert_eq!(
+            shortcut.time_comparison(speed),
+            "~18 seconds through the nether instead of ~2 min 23 s directly (Sprinting)",
+        );
+    }
+
+    #[test]
+    fn test_format_travel_time() {
+        assert_eq!(format_travel_time(4.6), "~5 seconds");
+        assert_eq!(format_travel_time(200.0), "~3 min 20 s");
+        assert_eq!(format_travel_time(3725.0), "~1 h 2 min");
+    }
+}
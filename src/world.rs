@@ -1,3 +1,5 @@
+use std::collections::hash_map::Entry;
+use std::collections::{HashMap, HashSet};
 use std::fmt;
 use std::ops::{Index, IndexMut, RangeInclusive};
 
@@ -5,7 +7,11 @@ use itertools::Itertools;
 use serde::{Deserialize, Serialize};
 use smallvec::{SmallVec, smallvec};
 
-use crate::{Axis, BlockPos, BlockRegion, Portal, WorldPos};
+use crate::links::LinkReport;
+use crate::{
+    Area, Axis, BlockPos, BlockRegion, Entity, NewPortalPlacement, Portal, PortalId, Route,
+    WorldPos, WorldRegion,
+};
 
 /// Overworld or nether.
 #[derive(Serialize, Deserialize, Debug, Default, Copy, Clone, PartialEq, Eq, Hash)]
@@ -56,6 +62,29 @@ impl Dimension {
         self.y_min()..=self.y_max()
     }
 
+    /// Returns the range of Y coordinates that an entity can be at, which
+    /// includes standing on top of the highest block.
+    pub fn entity_y_range(self) -> RangeInclusive<f64> {
+        self.y_min() as f64..=self.y_max() as f64 + 1.0
+    }
+    /// Clamps `y` to [`Dimension::entity_y_range()`].
+    pub fn clamp_entity_y(self, y: f64) -> f64 {
+        let y_range = self.entity_y_range();
+        y.clamp(*y_range.start(), *y_range.end())
+    }
+
+    /// Returns the range of Y coordinates at which Minecraft builds an obsidian
+    /// platform for a new portal when there is no suitable space nearby.
+    ///
+    /// This is at least Y=70 and at least 10 blocks below the top of the
+    /// dimension, which in the nether is the bedrock ceiling.
+    pub fn new_portal_platform_y_range(self) -> RangeInclusive<i64> {
+        match self {
+            Dimension::Overworld => 70..=self.y_max() - 9,
+            Dimension::Nether => 70..=118,
+        }
+    }
+
     /// Returns the other dimension.
     pub fn other(self) -> Dimension {
         match self {
@@ -80,36 +109,576 @@ impl Dimension {
 }
 
 /// Minecraft world.
-#[derive(Serialize, Deserialize, Debug, Default, Clone, PartialEq)]
+///
+/// Comparing worlds ignores which portals are expanded, the plot layout, and
+/// the display settings, so that changing them does not create an undo step or
+/// require links to be recalculated.
+#[derive(Serialize, Deserialize, Debug, Default, Clone)]
 pub struct World {
     /// Portals in each dimension.
     pub portals: WorldPortals,
     /// Test points in each dimension.
     #[serde(default)]
     pub test_points: WorldTestPoints,
+    /// Route annotations in each dimension.
+    #[serde(default)]
+    pub routes: ListPerDimension<Route>,
+    /// Area annotations in each dimension.
+    #[serde(default)]
+    pub areas: ListPerDimension<Area>,
+    /// Portal that each portal is intended to link to, keyed by the ID of the
+    /// source portal.
+    ///
+    /// This is only saved along with portal IDs, because portal IDs are
+    /// reassigned when loading. See [`World::to_json()`].
+    #[serde(skip)]
+    pub intended_links: HashMap<PortalId, PortalId>,
+    /// Portals that are expanded in the portal lists.
+    ///
+    /// This is saved using the index of each portal in its list, because
+    /// portal IDs are not always saved. See [`World::to_json()`]. This is
+    /// ignored when comparing worlds.
+    #[serde(skip)]
+    pub expanded_portals: HashSet<PortalId>,
+    /// Display settings to apply while the world is open. This is ignored when
+    /// comparing worlds.
+    #[serde(default, skip_serializing_if = "DisplayOverrides::is_empty")]
+    pub display: DisplayOverrides,
+    /// Whether the ZY plot was last shown while the world was open, or `None`
+    /// to follow the preference. This is ignored when comparing worlds.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub show_zy_plot: Option<bool>,
 }
 
-/// List of portals in a Minecraft world.
-pub type WorldPortals = ListPerDimension<Portal>;
-/// List of positions in a Minecraft world.
-pub type WorldTestPoints = ListPerDimension<WorldPos>;
+impl PartialEq for World {
+    fn eq(&self, other: &Self) -> bool {
+        let World {
+            portals,
+            test_points,
+            routes,
+            areas,
+            intended_links,
+            expanded_portals: _,
+            display: _,
+            show_zy_plot: _,
+        } = self;
+        (portals, test_points, routes, areas, intended_links)
+            == (
+                &other.portals,
+                &other.test_points,
+                &other.routes,
+                &other.areas,
+                &other.intended_links,
+            )
+    }
+}
 
-/// List of objects per dimension.
-#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, Hash)]
-pub struct ListPerDimension<T> {
-    pub overworld: Vec<T>,
-    pub nether: Vec<T>,
+/// World along with the portals that are expanded in the portal lists, for
+/// serialization.
+#[derive(Serialize)]
+struct WorldJson<'a> {
+    #[serde(flatten)]
+    world: &'a World,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    expanded_portals: Option<ListPerDimension<usize>>,
+}
+
+/// Display settings saved with a world, such as for a presentation, which
+/// take precedence over the user's preferences while the world is open.
+/// Settings that are `None` fall back to the preferences.
+#[derive(Serialize, Deserialize, Debug, Default, Copy, Clone, PartialEq)]
+#[serde(default)]
+pub struct DisplayOverrides {
+    /// Dark or light theme.
+    pub theme: Option<Theme>,
+    /// Whether to show labels for all portals.
+    pub show_all_labels: Option<bool>,
+    /// Whether to show arrows for all links.
+    pub show_all_arrows: Option<bool>,
+    /// How to color the arrows for links.
+    pub arrow_coloring: Option<ArrowColoring>,
 }
-impl<T> Default for ListPerDimension<T> {
+
+impl DisplayOverrides {
+    /// Returns whether no settings are overridden.
+    pub fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+}
+
+/// Dark or light theme.
+#[derive(Serialize, Deserialize, Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum Theme {
+    /// Follow the system theme.
+    System,
+    /// Dark theme.
+    Dark,
+    /// Light theme.
+    Light,
+}
+
+/// How to color the arrows for links.
+#[derive(Serialize, Deserialize, Debug, Default, Copy, Clone, PartialEq, Eq, Hash)]
+#[allow(clippy::enum_variant_names)] // variant names are saved in preferences
+pub enum ArrowColoring {
+    /// Color by the portal that the link starts at.
+    #[default]
+    BySource,
+    /// Color by the portal that the link leads to.
+    ByDestination,
+    /// Color by the dimension that the link leads into.
+    ByDimension,
+    /// Color by whether the source portal links cleanly to one portal.
+    ByQuality,
+}
+
+/// Parts of a world to keep when importing only part of it.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct WorldFilter {
+    /// Whether to keep portals and intended links.
+    pub portals: bool,
+    /// Whether to keep test points.
+    pub test_points: bool,
+    /// Whether to keep route and area annotations.
+    pub annotations: bool,
+    /// Dimension to keep, or `None` to keep both.
+    pub dimension: Option<Dimension>,
+}
+
+impl Default for WorldFilter {
     fn default() -> Self {
         Self {
-            overworld: vec![],
-            nether: vec![],
+            portals: true,
+            test_points: true,
+            annotations: true,
+            dimension: None,
+        }
+    }
+}
+
+impl WorldFilter {
+    /// Returns whether the filter keeps everything.
+    pub fn keeps_everything(self) -> bool {
+        self == Self::default()
+    }
+    /// Returns whether the filter keeps anything in `dimension`.
+    fn keeps_dimension(self, dimension: Dimension) -> bool {
+        self.dimension.is_none_or(|d| d == dimension)
+    }
+}
+
+impl World {
+    /// Removes everything that is excluded by `filter`.
+    ///
+    /// Intended links are kept only if both portals are kept. Display settings
+    /// are kept only if everything is kept.
+    pub fn filter(&mut self, filter: WorldFilter) {
+        for dimension in [Dimension::Overworld, Dimension::Nether] {
+            let keep_dimension = filter.keeps_dimension(dimension);
+            if !(filter.portals && keep_dimension) {
+                self.portals[dimension].clear();
+            }
+            if !(filter.test_points && keep_dimension) {
+                self.test_points[dimension].clear();
+            }
+            if !(filter.annotations && keep_dimension) {
+                self.routes[dimension].clear();
+                self.areas[dimension].clear();
+            }
+        }
+        let ids: HashSet<PortalId> = self.portals.iter().map(|portal| portal.id).collect();
+        self.intended_links
+            .retain(|source, destination| ids.contains(source) && ids.contains(destination));
+        self.expanded_portals.retain(|id| ids.contains(id));
+        if !filter.keeps_everything() {
+            self.display = DisplayOverrides::default();
+        }
+    }
+
+    /// Replaces the parts of the world selected by `filter` with the same parts
+    /// of `other`, keeping everything else.
+    ///
+    /// Intended links are kept only if both portals are kept.
+    pub fn replace(&mut self, mut other: World, filter: WorldFilter) {
+        other.filter(filter);
+        for dimension in [Dimension::Overworld, Dimension::Nether] {
+            if !filter.keeps_dimension(dimension) {
+                continue;
+            }
+            if filter.portals {
+                self.portals[dimension] = std::mem::take(&mut other.portals[dimension])
+                    .into_iter()
+                    .map(|portal| Portal {
+                        file_id: None,
+                        ..portal
+                    })
+                    .collect();
+            }
+            if filter.test_points {
+                self.test_points[dimension] = std::mem::take(&mut other.test_points[dimension]);
+            }
+            if filter.annotations {
+                self.routes[dimension] = std::mem::take(&mut other.routes[dimension]);
+                self.areas[dimension] = std::mem::take(&mut other.areas[dimension]);
+            }
+        }
+        let ids: HashSet<PortalId> = self.portals.iter().map(|portal| portal.id).collect();
+        self.intended_links
+            .retain(|source, destination| ids.contains(source) && ids.contains(destination));
+        self.intended_links.extend(other.intended_links);
+        self.expanded_portals.retain(|id| ids.contains(id));
+        self.expanded_portals.extend(other.expanded_portals);
+        if filter.keeps_everything() {
+            self.display = other.display;
+            self.show_zy_plot = other.show_zy_plot;
+        }
+    }
+
+    /// Moves all portals and test points by `offset`, which is measured in
+    /// `offset_dimension` and scaled for the other dimension.
+    ///
+    /// Portals are moved by whole blocks and kept within the build limits.
+    pub fn translate(&mut self, offset: WorldPos, offset_dimension: Dimension) {
+        for dimension in [Dimension::Overworld, Dimension::Nether] {
+            let offset = offset.convert_dimension(offset_dimension, dimension);
+            for portal in &mut self.portals[dimension] {
+                portal.translate(offset.round(), dimension);
+            }
+            for test_point in &mut self.test_points[dimension] {
+                *test_point += offset;
+                let y_range = dimension.entity_y_range();
+                test_point.y = test_point.y.clamp(*y_range.start(), *y_range.end());
+            }
+            for route in &mut self.routes[dimension] {
+                for point in &mut route.points {
+                    *point += offset;
+                }
+            }
+            for area in &mut self.areas[dimension] {
+                area.region.min += offset.round();
+                area.region.max += offset.round();
+            }
+        }
+    }
+
+    /// Appends all portals, test points, and annotations from `other`.
+    ///
+    /// Incoming portals are given fresh IDs so that they cannot clash with
+    /// existing ones.
+    pub fn merge(&mut self, other: World) {
+        let World {
+            mut portals,
+            mut test_points,
+            mut routes,
+            mut areas,
+            intended_links,
+            expanded_portals,
+            display: _,
+            show_zy_plot: _,
+        } = other;
+        let mut new_ids = HashMap::new();
+        for dimension in [Dimension::Overworld, Dimension::Nether] {
+            self.portals[dimension].extend(
+                std::mem::take(&mut portals[dimension])
+                    .into_iter()
+                    .map(|portal| {
+                        let id = PortalId::new();
+                        new_ids.insert(portal.id, id);
+                        Portal {
+                            id,
+                            file_id: None,
+                            ..portal
+                        }
+                    }),
+            );
+            self.test_points[dimension].append(&mut test_points[dimension]);
+            self.routes[dimension].append(&mut routes[dimension]);
+            self.areas[dimension].append(&mut areas[dimension]);
+        }
+        for (source, destination) in intended_links {
+            if let (Some(&source), Some(&destination)) =
+                (new_ids.get(&source), new_ids.get(&destination))
+            {
+                self.intended_links.insert(source, destination);
+            }
+        }
+        self.expanded_portals
+            .extend(expanded_portals.iter().filter_map(|id| new_ids.get(id)));
+    }
+
+    /// Returns the ID of each portal in a saved file.
+    ///
+    /// Portals keep the file ID they were loaded with. Portals without one, and
+    /// portals whose file ID is already used by an earlier portal, are given
+    /// the next unused IDs in order.
+    pub fn file_ids(&self) -> HashMap<PortalId, u64> {
+        let mut used = HashSet::new();
+        let mut file_ids = HashMap::new();
+        for portal in self.portals.iter() {
+            if let Some(file_id) = portal.file_id
+                && used.insert(file_id)
+            {
+                file_ids.insert(portal.id, file_id);
+            }
+        }
+        let mut next_file_id = used.iter().max().map_or(1, |&max| max + 1);
+        for portal in self.portals.iter() {
+            if let Entry::Vacant(e) = file_ids.entry(portal.id) {
+                e.insert(next_file_id);
+                next_file_id += 1;
+            }
+        }
+        file_ids
+    }
+
+    /// Stores the result of [`World::file_ids()`] in each portal.
+    pub fn assign_file_ids(&mut self) {
+        let file_ids = self.file_ids();
+        for dimension in [Dimension::Overworld, Dimension::Nether] {
+            for portal in &mut self.portals[dimension] {
+                portal.file_id = file_ids.get(&portal.id).copied();
+            }
         }
     }
+
+    /// Serializes the world to pretty-printed JSON.
+    ///
+    /// Portal IDs are only unique within a session, so they are not normally
+    /// saved. If `save_ids` is `true` or if there are any intended links, then
+    /// each portal is saved with its ID from [`World::file_ids()`], which is
+    /// kept when the file is loaded again, and intended links are saved using
+    /// those IDs.
+    ///
+    /// Expanded portals are always saved using the index of each portal in its
+    /// list.
+    pub fn to_json(&self, save_ids: bool) -> serde_json::Result<String> {
+        let expanded_portals = ListPerDimension {
+            overworld: self.expanded_portal_indices(Dimension::Overworld),
+            nether: self.expanded_portal_indices(Dimension::Nether),
+        };
+        let world_json = WorldJson {
+            world: self,
+            expanded_portals: (!expanded_portals.overworld.is_empty()
+                || !expanded_portals.nether.is_empty())
+            .then_some(expanded_portals),
+        };
+        if !save_ids && self.intended_links.is_empty() {
+            return serde_json::to_string_pretty(&world_json);
+        }
+        let mut value = serde_json::to_value(&world_json)?;
+        let file_ids = self.file_ids();
+        for (dimension, key) in [
+            (Dimension::Overworld, "overworld"),
+            (Dimension::Nether, "nether"),
+        ] {
+            let portals = value["portals"][key].as_array_mut();
+            for (portal, value) in
+                std::iter::zip(&self.portals[dimension], portals.into_iter().flatten())
+            {
+                if let Some(value) = value.as_object_mut()
+                    && let Some(&file_id) = file_ids.get(&portal.id)
+                {
+                    value.insert("id".to_owned(), file_id.into());
+                }
+            }
+        }
+        let intended_links = self
+            .intended_links
+            .iter()
+            .filter_map(|(source, destination)| {
+                Some([*file_ids.get(source)?, *file_ids.get(destination)?])
+            })
+            .sorted()
+            .collect_vec();
+        if let Some(value) = value.as_object_mut() {
+            value.insert(
+                "intended_links".to_owned(),
+                serde_json::to_value(intended_links)?,
+            );
+        }
+        serde_json::to_string_pretty(&value)
+    }
+
+    /// Deserializes a world from JSON.
+    ///
+    /// Portals are always given new IDs, so they cannot collide with portals
+    /// already loaded in this session. If the JSON contains portal IDs from
+    /// [`World::to_json()`], then they are kept as file IDs and used to restore
+    /// intended links. If two portals have the same ID, then only the first one
+    /// keeps it and can be the source or destination of an intended link.
+    pub fn from_json(s: &str) -> serde_json::Result<World> {
+        Ok(Self::from_json_with_duplicate_ids(s)?.0)
+    }
+
+    /// Deserializes a world from JSON, like [`World::from_json()`], and also
+    /// returns the portal IDs in the JSON that are used by more than one
+    /// portal.
+    pub fn from_json_with_duplicate_ids(s: &str) -> serde_json::Result<(World, Vec<u64>)> {
+        let mut world: World = serde_json::from_str(s)?;
+        let value: serde_json::Value = serde_json::from_str(s)?;
+        if let Some(expanded_portals) = value.get("expanded_portals") {
+            let expanded_portals: ListPerDimension<usize> =
+                serde_json::from_value(expanded_portals.clone())?;
+            for dimension in [Dimension::Overworld, Dimension::Nether] {
+                let portals = &world.portals[dimension];
+                world.expanded_portals.extend(
+                    (expanded_portals[dimension].iter()).filter_map(|&i| Some(portals.get(i)?.id)),
+                );
+            }
+        }
+
+        let mut ids = HashMap::new();
+        let mut duplicate_ids = vec![];
+        for (dimension, key) in [
+            (Dimension::Overworld, "overworld"),
+            (Dimension::Nether, "nether"),
+        ] {
+            let values = value["portals"][key].as_array().into_iter().flatten();
+            for (portal, value) in std::iter::zip(&mut world.portals[dimension], values) {
+                let Some(file_id) = value["id"].as_u64() else {
+                    continue;
+                };
+                match ids.entry(file_id) {
+                    Entry::Occupied(_) => duplicate_ids.push(file_id),
+                    Entry::Vacant(e) => {
+                        e.insert(portal.id);
+                        portal.file_id = Some(file_id);
+                    }
+                }
+            }
+        }
+        let intended_links = value["intended_links"].as_array().into_iter().flatten();
+        for link in intended_links {
+            if let (Some(source), Some(destination)) = (link[0].as_u64(), link[1].as_u64())
+                && let (Some(&source), Some(&destination)) =
+                    (ids.get(&source), ids.get(&destination))
+            {
+                world.intended_links.insert(source, destination);
+            }
+        }
+        duplicate_ids.sort_unstable();
+        duplicate_ids.dedup();
+        Ok((world, duplicate_ids))
+    }
+
+    /// Returns the index of each expanded portal in `dimension`.
+    fn expanded_portal_indices(&self, dimension: Dimension) -> Vec<usize> {
+        self.portals[dimension]
+            .iter()
+            .positions(|portal| self.expanded_portals.contains(&portal.id))
+            .collect()
+    }
+
+    /// Computes every link in the world for `entity`, using the default link
+    /// search step limit and new portal placement.
+    pub fn analyze(&self, entity: Entity) -> LinkReport {
+        LinkReport::new(
+            self,
+            entity,
+            DEFAULT_LINK_SEARCH_STEP_LIMIT,
+            NewPortalPlacement::default(),
+        )
+    }
+
+    /// Returns a small example world with a few linked portals and
+    /// annotations.
+    pub fn example() -> World {
+        World::from_json(include_str!("example_world.json")).expect("invalid example world")
+    }
+
+    /// Returns the smallest region containing all portals and test points,
+    /// converted to `dimension`, or `None` if there are none.
+    pub fn bounding_region(&self, dimension: Dimension) -> Option<WorldRegion> {
+        let portal_regions = [Dimension::Overworld, Dimension::Nether]
+            .into_iter()
+            .flat_map(|dim| {
+                self.portals[dim]
+                    .iter()
+                    .map(move |p| WorldRegion::from(p.region).convert_dimension(dim, dimension))
+            });
+        let test_point_regions = [Dimension::Overworld, Dimension::Nether]
+            .into_iter()
+            .flat_map(|dim| {
+                self.test_points[dim].iter().map(move |&pos| {
+                    let pos = pos.convert_dimension(dim, dimension);
+                    WorldRegion { min: pos, max: pos }
+                })
+            });
+        itertools::chain(portal_regions, test_point_regions).reduce(WorldRegion::union)
+    }
+}
+
+/// List of portals in a Minecraft world.
+pub type WorldPortals = ListPerDimension<Portal>;
+
+/// Lookup from portal ID to the dimension and index of the portal in
+/// [`WorldPortals`], which is only rebuilt when portals are added, removed, or
+/// reordered.
+#[derive(Debug, Default, Clone)]
+pub struct PortalIndex {
+    ids: ListPerDimension<PortalId>,
+    positions: HashMap<PortalId, (Dimension, usize)>,
+}
+impl PortalIndex {
+    /// Rebuilds the lookup if the list of portals has changed.
+    pub fn update(&mut self, portals: &WorldPortals) {
+        let is_current = [Dimension::Overworld, Dimension::Nether]
+            .into_iter()
+            .all(|dim| {
+                self.ids[dim]
+                    .iter()
+                    .copied()
+                    .eq(portals[dim].iter().map(|p| p.id))
+            });
+        if is_current {
+            return;
+        }
+        self.positions.clear();
+        for dim in [Dimension::Overworld, Dimension::Nether] {
+            self.ids[dim] = portals[dim].iter().map(|p| p.id).collect();
+            for (i, &id) in self.ids[dim].iter().enumerate() {
+                self.positions.insert(id, (dim, i));
+            }
+        }
+    }
+
+    /// Returns the dimension and index of the portal with ID `id`.
+    ///
+    /// If the lookup is out of date, this falls back to searching `portals`.
+    pub fn position(&self, portals: &WorldPortals, id: PortalId) -> Option<(Dimension, usize)> {
+        match self.positions.get(&id) {
+            Some(&(dim, i)) if portals[dim].get(i).is_some_and(|p| p.id == id) => Some((dim, i)),
+            _ => portals.position(id),
+        }
+    }
+
+    /// Returns the portal with ID `id`, along with its dimension.
+    ///
+    /// If the lookup is out of date, this falls back to searching `portals`.
+    pub fn get<'a>(
+        &self,
+        portals: &'a WorldPortals,
+        id: PortalId,
+    ) -> Option<(Dimension, &'a Portal)> {
+        let (dim, i) = self.position(portals, id)?;
+        Some((dim, &portals[dim][i]))
+    }
+}
+/// List of positions in a Minecraft world.
+pub type WorldTestPoints = ListPerDimension<WorldPos>;
+
+/// List of objects per dimension.
+pub type ListPerDimension<T> = PerDimension<Vec<T>>;
+
+/// Value per dimension.
+#[derive(Serialize, Deserialize, Debug, Default, Copy, Clone, PartialEq, Eq, Hash)]
+#[allow(missing_docs)]
+pub struct PerDimension<T> {
+    pub overworld: T,
+    pub nether: T,
 }
-impl<T> Index<Dimension> for ListPerDimension<T> {
-    type Output = Vec<T>;
+impl<T> Index<Dimension> for PerDimension<T> {
+    type Output = T;
 
     fn index(&self, index: Dimension) -> &Self::Output {
         match index {
@@ -118,7 +687,7 @@ impl<T> Index<Dimension> for ListPerDimension<T> {
         }
     }
 }
-impl<T> IndexMut<Dimension> for ListPerDimension<T> {
+impl<T> IndexMut<Dimension> for PerDimension<T> {
     fn index_mut(&mut self, index: Dimension) -> &mut Self::Output {
         match index {
             Dimension::Overworld => &mut self.overworld,
@@ -150,6 +719,40 @@ pub trait ConvertDimension: Sized {
 }
 
 impl WorldPortals {
+    /// Iterates over all portals in the overworld and then the nether.
+    pub fn iter(&self) -> impl Iterator<Item = &Portal> {
+        itertools::chain(&self.overworld, &self.nether)
+    }
+
+    /// Iterates over all portals in the overworld and then the nether, along
+    /// with the dimension of each.
+    pub fn iter_with_dimension(&self) -> impl Iterator<Item = (Dimension, &Portal)> {
+        [Dimension::Overworld, Dimension::Nether]
+            .into_iter()
+            .flat_map(|dimension| self[dimension].iter().map(move |p| (dimension, p)))
+    }
+
+    /// Returns the dimension and index of the portal with ID `id`.
+    ///
+    /// This searches every portal; use [`PortalIndex`] for repeated lookups.
+    pub fn position(&self, id: PortalId) -> Option<(Dimension, usize)> {
+        [Dimension::Overworld, Dimension::Nether]
+            .into_iter()
+            .find_map(|dim| Some((dim, self[dim].iter().position(|p| p.id == id)?)))
+    }
+
+    /// Returns the portal with ID `id`, along with its dimension.
+    pub fn get(&self, id: PortalId) -> Option<(Dimension, &Portal)> {
+        let (dim, i) = self.position(id)?;
+        Some((dim, &self[dim][i]))
+    }
+
+    /// Returns the portal with ID `id` mutably, along with its dimension.
+    pub fn get_mut(&mut self, id: PortalId) -> Option<(Dimension, &mut Portal)> {
+        let (dim, i) = self.position(id)?;
+        Some((dim, &mut self[dim][i]))
+    }
+
     /// Returns the portal an entity will teleport to. If multiple portals are
     /// equidistant, they are all returned.
     pub fn entity_destinations(
@@ -177,7 +780,12 @@ impl WorldPortals {
             .collect()
     }
 
-    #[cfg(test)]
+    /// Returns the set of portals that are reachable from `destination_region`
+    /// by checking every block in it.
+    ///
+    /// This is much slower than [`Self::portal_destinations()`] for typical
+    /// layouts, but it takes time proportional to the size of the region times
+    /// the number of portals, regardless of how the portals are arranged.
     pub(crate) fn portal_destinations_naive(
         &self,
         destination_dimension: Dimension,
@@ -186,6 +794,7 @@ impl WorldPortals {
         let candidates = &self[destination_dimension];
 
         let mut candidates_in_range = vec![false; candidates.len()];
+        let mut min_distances = vec![i64::MAX; candidates.len()];
 
         let mut distances = vec![0; candidates.len()]; // buffer for reuse
         let mut new_portal = false;
@@ -198,6 +807,7 @@ impl WorldPortals {
                 } else {
                     i64::MAX
                 };
+                min_distances[i] = min_distances[i].min(distances[i]);
             }
             let min_distance = distances.iter().copied().min().unwrap_or(i64::MAX);
             if min_distance == i64::MAX {
@@ -209,15 +819,12 @@ impl WorldPortals {
             }
         }
 
-        let existing_portals = std::iter::zip(candidates, candidates_in_range)
-            .filter(|(_, in_range)| *in_range)
-            .map(|(p, _)| p)
-            .collect();
-
-        PortalDestinations {
-            existing_portals,
+        PortalDestinations::new(
+            itertools::izip!(candidates, candidates_in_range, min_distances)
+                .filter(|(_, in_range, _)| *in_range)
+                .map(|(p, _, distance_sq)| (p, distance_sq)),
             new_portal,
-        }
+        )
     }
 
     /// Returns the set of portals that are reachable from `destination_region`.
@@ -225,10 +832,27 @@ impl WorldPortals {
         &self,
         destination_dimension: Dimension,
         destination_region: BlockRegion,
+    ) -> PortalDestinations<'_> {
+        self.portal_destinations_with_step_limit(
+            destination_dimension,
+            destination_region,
+            DEFAULT_LINK_SEARCH_STEP_LIMIT,
+        )
+    }
+
+    /// Returns the set of portals that are reachable from `destination_region`,
+    /// falling back to [`Self::portal_destinations_naive()`] if the search
+    /// takes more than `max_steps` steps.
+    pub fn portal_destinations_with_step_limit(
+        &self,
+        destination_dimension: Dimension,
+        destination_region: BlockRegion,
+        max_steps: usize,
     ) -> PortalDestinations<'_> {
         let candidates = &self[destination_dimension];
 
         let mut confirmed_reachable = vec![false; candidates.len()];
+        let mut min_distances = vec![i64::MAX; candidates.len()];
         let mut may_generate_new_portal = false;
 
         let mut steps = 0;
@@ -239,31 +863,57 @@ impl WorldPortals {
             candidates,
             (0..candidates.len()).collect(),
             &mut confirmed_reachable,
+            &mut min_distances,
             &mut may_generate_new_portal,
             &mut steps,
+            max_steps,
         );
 
-        PortalDestinations {
-            existing_portals: confirmed_reachable
+        if steps > max_steps {
+            log::debug!(
+                "portal search took more than {max_steps} steps; \
+                 falling back to checking every block",
+            );
+            return self.portal_destinations_naive(destination_dimension, destination_region);
+        }
+
+        PortalDestinations::new(
+            confirmed_reachable
                 .iter()
                 .positions(|b| *b)
-                .map(|i| &candidates[i])
-                .collect(),
-            new_portal: may_generate_new_portal,
-        }
+                .map(|i| (&candidates[i], min_distances[i])),
+            may_generate_new_portal,
+        )
     }
 }
 
+/// Default maximum number of subregions to search in
+/// [`mark_reachable_portals()`] before falling back to the naive method.
+///
+/// Typical layouts take fewer than 100 steps, but adversarial arrangements of
+/// many small portals can take far more.
+pub const DEFAULT_LINK_SEARCH_STEP_LIMIT: usize = 4096;
+
+/// Marks the candidates that are reachable from `destination_region`.
+///
+/// `min_distances` is lowered to the squared distance from the region to each
+/// candidate in range of it, which for the initial region is the distance
+/// that determines the order of [`PortalDestinations::existing_portals`].
 fn mark_reachable_portals(
     destination_dimension: Dimension,
     destination_region: BlockRegion,
     candidates: &[Portal],
     mut candidates_that_might_be_reachable: SmallVec<[usize; 8]>,
     confirmed_reachable: &mut [bool],
+    min_distances: &mut [i64],
     may_generate_new_portal: &mut bool,
     steps: &mut usize,
+    max_steps: usize,
 ) {
     *steps += 1;
+    if *steps > max_steps {
+        return; // give up; the caller will fall back to the naive method
+    }
 
     // Filter for portals within the search range
     candidates_that_might_be_reachable.retain(|&mut p| {
@@ -281,8 +931,9 @@ fn mark_reachable_portals(
         .min()
         .unwrap_or(i64::MAX);
     candidates_that_might_be_reachable.retain(|&mut p| {
-        destination_region.min_euclidean_distance_sq_to(candidates[p].region)
-            <= smallest_max_distance
+        let distance = destination_region.min_euclidean_distance_sq_to(candidates[p].region);
+        min_distances[p] = min_distances[p].min(distance);
+        distance <= smallest_max_distance
     });
 
     let corners = destination_region.corners();
@@ -339,8 +990,10 @@ fn mark_reachable_portals(
                         candidates,
                         candidates_that_might_be_reachable.clone(),
                         confirmed_reachable,
+                        min_distances,
                         may_generate_new_portal,
                         steps,
+                        max_steps,
                     );
                 }
             }
@@ -373,8 +1026,10 @@ fn mark_reachable_portals(
                                 candidates,
                                 candidates_that_might_be_reachable.clone(),
                                 confirmed_reachable,
+                                min_distances,
                                 may_generate_new_portal,
                                 steps,
+                                max_steps,
                             );
                         }
                         return;
@@ -408,9 +1063,31 @@ fn minima_by_opt_key<I: IntoIterator, C: Ord>(
 
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub struct PortalDestinations<'a> {
+    /// Reachable portals, sorted from closest to farthest.
     pub existing_portals: Vec<&'a Portal>,
+    /// Squared minimum distance from the destination region to each portal in
+    /// `existing_portals`, as computed during the search.
+    pub distances_sq: Vec<i64>,
     pub new_portal: bool,
 }
+impl<'a> PortalDestinations<'a> {
+    /// Constructs destinations from reachable portals and their squared
+    /// distances, sorting them from closest to farthest.
+    fn new(
+        existing_portals: impl IntoIterator<Item = (&'a Portal, i64)>,
+        new_portal: bool,
+    ) -> Self {
+        let (existing_portals, distances_sq) = existing_portals
+            .into_iter()
+            .sorted_by_key(|&(_, distance_sq)| distance_sq)
+            .unzip();
+        Self {
+            existing_portals,
+            distances_sq,
+            new_portal,
+        }
+    }
+}
 
 #[cfg(test)]
 mod tests {
@@ -438,6 +1115,421 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_filter() {
+        let mut world = World::default();
+        world.portals.overworld = vec![Portal::new_test(([0, 64, 0], [0, 66, 1]))];
+        world.portals.nether = vec![Portal::new_test(([1, 64, 0], [1, 66, 1]))];
+        world.test_points.overworld = vec![WorldPos::default()];
+        world.test_points.nether = vec![WorldPos::default()];
+        world.intended_links =
+            HashMap::from([(world.portals.overworld[0].id, world.portals.nether[0].id)]);
+
+        let mut unfiltered = world.clone();
+        unfiltered.filter(WorldFilter::default());
+        assert_eq!(unfiltered, world);
+
+        let mut portals_only = world.clone();
+        portals_only.filter(WorldFilter {
+            test_points: false,
+            ..Default::default()
+        });
+        assert_eq!(portals_only.portals, world.portals);
+        assert_eq!(portals_only.intended_links, world.intended_links);
+        assert!(portals_only.test_points.overworld.is_empty());
+        assert!(portals_only.test_points.nether.is_empty());
+
+        let mut nether_only = world.clone();
+        nether_only.filter(WorldFilter {
+            dimension: Some(Dimension::Nether),
+            ..Default::default()
+        });
+        assert!(nether_only.portals.overworld.is_empty());
+        assert_eq!(nether_only.portals.nether, world.portals.nether);
+        assert!(nether_only.test_points.overworld.is_empty());
+        assert_eq!(nether_only.test_points.nether.len(), 1);
+        assert!(nether_only.intended_links.is_empty());
+    }
+
+    #[test]
+    fn test_replace() {
+        let mut world = World::default();
+        world.portals.overworld = vec![Portal::new_test(([0, 64, 0], [0, 66, 1]))];
+        world.portals.nether = vec![Portal::new_test(([1, 64, 0], [1, 66, 1]))];
+        world.test_points.overworld = vec![WorldPos::default()];
+        world.intended_links =
+            HashMap::from([(world.portals.overworld[0].id, world.portals.nether[0].id)]);
+        world.expanded_portals = HashSet::from([world.portals.nether[0].id]);
+        world.display.show_all_labels = Some(true);
+
+        let mut other = World::default();
+        other.portals.nether = vec![Portal::new_test(([2, 64, 0], [2, 66, 1]))];
+        other.test_points.overworld = vec![WorldPos {
+            x: 1.0,
+            y: 2.0,
+            z: 3.0,
+        }];
+        other.test_points.nether = vec![WorldPos::default()];
+
+        // Replacing only test points keeps all portals.
+        let mut test_points_only = world.clone();
+        test_points_only.replace(
+            other.clone(),
+            WorldFilter {
+                portals: false,
+                ..Default::default()
+            },
+        );
+        assert_eq!(test_points_only.portals, world.portals);
+        assert_eq!(test_points_only.intended_links, world.intended_links);
+        assert_eq!(test_points_only.test_points, other.test_points);
+        assert_eq!(test_points_only.display, world.display);
+
+        // Replacing nether portals keeps overworld portals and drops links to
+        // the replaced portals.
+        let mut nether_only = world.clone();
+        nether_only.replace(
+            other.clone(),
+            WorldFilter {
+                dimension: Some(Dimension::Nether),
+                ..Default::default()
+            },
+        );
+        assert_eq!(nether_only.portals.overworld, world.portals.overworld);
+        assert_eq!(nether_only.portals.nether, other.portals.nether);
+        assert_eq!(
+            nether_only.test_points.overworld,
+            world.test_points.overworld
+        );
+        assert_eq!(nether_only.test_points.nether, other.test_points.nether);
+        assert!(nether_only.intended_links.is_empty());
+        assert!(nether_only.expanded_portals.is_empty());
+
+        // Replacing everything replaces the display settings too.
+        let mut everything = world.clone();
+        everything.replace(other.clone(), WorldFilter::default());
+        assert_eq!(everything, other);
+        assert_eq!(everything.display, other.display);
+    }
+
+    #[test]
+    fn test_to_json_with_ids() {
+        let mut world = World::default();
+        world.portals.overworld = vec![
+            Portal::new_test(([0, 64, 0], [0, 66, 1])),
+            Portal::new_test(([8, 64, 0], [8, 66, 1])),
+        ];
+        world.portals.nether = vec![Portal::new_test(([1, 64, 0], [1, 66, 1]))];
+
+        let json = world.to_json(true).expect("serialization failed");
+        let value: serde_json::Value = serde_json::from_str(&json).expect("invalid JSON");
+        assert_eq!(value["portals"]["overworld"][0]["id"], 1);
+        assert_eq!(value["portals"]["overworld"][1]["id"], 2);
+        assert_eq!(value["portals"]["nether"][0]["id"], 3);
+
+        // IDs do not depend on in-memory IDs.
+        let mut copy = world.clone();
+        for portal in &mut copy.portals.overworld {
+            portal.id = crate::PortalId::new();
+        }
+        assert_eq!(copy.to_json(true).expect("serialization failed"), json);
+    }
+
+    #[test]
+    fn test_expanded_portals_json() {
+        let mut world = World::default();
+        world.portals.overworld = vec![
+            Portal::new_test(([0, 64, 0], [0, 66, 1])),
+            Portal::new_test(([8, 64, 0], [8, 66, 1])),
+        ];
+        world.portals.nether = vec![Portal::new_test(([1, 64, 0], [1, 66, 1]))];
+
+        let json = world.to_json(false).expect("serialization failed");
+        assert!(!json.contains("expanded_portals"));
+        assert!(!json.contains("built"));
+
+        let collapsed = world.clone();
+        world.expanded_portals =
+            HashSet::from([world.portals.overworld[1].id, world.portals.nether[0].id]);
+        assert_eq!(
+            world, collapsed,
+            "expanded portals should not affect equality"
+        );
+        for save_ids in [false, true] {
+            let json = world.to_json(save_ids).expect("serialization failed");
+            let value: serde_json::Value = serde_json::from_str(&json).expect("invalid JSON");
+            assert_eq!(
+                value["expanded_portals"]["overworld"],
+                serde_json::json!([1])
+            );
+            assert_eq!(value["expanded_portals"]["nether"], serde_json::json!([0]));
+
+            let loaded = World::from_json(&json).expect("deserialization failed");
+            let expected =
+                HashSet::from([loaded.portals.overworld[1].id, loaded.portals.nether[0].id]);
+            assert_eq!(loaded.expanded_portals, expected);
+        }
+    }
+
+    #[test]
+    fn test_file_ids_are_kept() {
+        let mut world = World::default();
+        world.portals.overworld = vec![
+            Portal::new_test(([0, 64, 0], [0, 66, 1])),
+            Portal::new_test(([8, 64, 0], [8, 66, 1])),
+        ];
+        world.portals.nether = vec![Portal::new_test(([1, 64, 0], [1, 66, 1]))];
+        let json = world.to_json(true).expect("serialization failed");
+
+        let mut loaded = World::from_json(&json).expect("deserialization failed");
+        loaded.portals.overworld.remove(0);
+        let mut copy = loaded.portals.nether[0].clone();
+        copy.id = PortalId::new();
+        loaded.portals.nether.push(copy);
+        loaded
+            .portals
+            .overworld
+            .push(Portal::new_test(([16, 64, 0], [16, 66, 1])));
+
+        let json = loaded.to_json(true).expect("serialization failed");
+        let value: serde_json::Value = serde_json::from_str(&json).expect("invalid JSON");
+        // Remaining portals keep their IDs, and the new portal and the copy
+        // get new ones.
+        assert_eq!(value["portals"]["overworld"][0]["id"], 2);
+        assert_eq!(value["portals"]["overworld"][1]["id"], 4);
+        assert_eq!(value["portals"]["nether"][0]["id"], 3);
+        assert_eq!(value["portals"]["nether"][1]["id"], 5);
+
+        loaded.assign_file_ids();
+        let file_ids = loaded.portals.iter().map(|p| p.file_id).collect_vec();
+        assert_eq!(file_ids, [Some(2), Some(4), Some(3), Some(5)]);
+    }
+
+    #[test]
+    fn test_from_json() {
+        let mut world = World::default();
+        world.portals.overworld = vec![
+            Portal::new_test(([0, 64, 0], [0, 66, 1])),
+            Portal::new_test(([8, 64, 0], [8, 66, 1])),
+        ];
+        world.portals.nether = vec![Portal::new_test(([1, 64, 0], [1, 66, 1]))];
+        world.intended_links = HashMap::from([
+            (world.portals.overworld[1].id, world.portals.nether[0].id),
+            (world.portals.nether[0].id, world.portals.overworld[0].id),
+        ]);
+
+        let json = world.to_json(true).expect("serialization failed");
+        let loaded = World::from_json(&json).expect("deserialization failed");
+        let regions = |w: &World| w.portals.iter().map(|p| p.region).collect_vec();
+        assert_eq!(regions(&loaded), regions(&world));
+        assert_ne!(loaded.portals.nether[0].id, world.portals.nether[0].id);
+        assert_eq!(
+            loaded.intended_links,
+            HashMap::from([
+                (loaded.portals.overworld[1].id, loaded.portals.nether[0].id),
+                (loaded.portals.nether[0].id, loaded.portals.overworld[0].id),
+            ]),
+        );
+        assert_eq!(loaded.to_json(true).expect("serialization failed"), json);
+
+        // Intended links are saved even if IDs are not requested.
+        let json = world.to_json(false).expect("serialization failed");
+        let loaded = World::from_json(&json).expect("deserialization failed");
+        assert_eq!(
+            loaded.intended_links,
+            HashMap::from([
+                (loaded.portals.overworld[1].id, loaded.portals.nether[0].id),
+                (loaded.portals.nether[0].id, loaded.portals.overworld[0].id),
+            ]),
+        );
+
+        // Without intended links, IDs are only saved if requested.
+        let mut unlinked = world.clone();
+        unlinked.intended_links.clear();
+        let json = unlinked.to_json(false).expect("serialization failed");
+        assert!(!json.contains("\"id\""));
+        assert!(!json.contains("intended_links"));
+
+        // Duplicate IDs refer to the first portal.
+        let mut value: serde_json::Value =
+            serde_json::from_str(&world.to_json(true).expect("serialization failed"))
+                .expect("invalid JSON");
+        value["portals"]["overworld"][1]["id"] = 1.into();
+        value["intended_links"] = serde_json::json!([[3, 1], [2, 1]]);
+        let json = value.to_string();
+        let (loaded, duplicate_ids) =
+            World::from_json_with_duplicate_ids(&json).expect("deserialization failed");
+        assert_eq!(duplicate_ids, [1]);
+        assert_eq!(loaded.portals.overworld[0].file_id, Some(1));
+        assert_eq!(loaded.portals.overworld[1].file_id, None);
+        assert_eq!(
+            loaded.intended_links,
+            HashMap::from([(loaded.portals.nether[0].id, loaded.portals.overworld[0].id)]),
+        );
+    }
+
+    #[test]
+    fn test_portal_index() {
+        let a = Portal::new_test(([0, 64, 0], [0, 66, 1]));
+        let b = Portal::new_test(([8, 64, 0], [8, 66, 1]));
+        let c = Portal::new_test(([1, 64, 0], [1, 66, 1]));
+        let mut portals = WorldPortals {
+            overworld: vec![a.clone(), b.clone()],
+            nether: vec![c.clone()],
+        };
+        assert_eq!(
+            portals
+                .iter_with_dimension()
+                .map(|(d, p)| (d, p.id))
+                .collect_vec(),
+            [
+                (Dimension::Overworld, a.id),
+                (Dimension::Overworld, b.id),
+                (Dimension::Nether, c.id),
+            ],
+        );
+
+        let mut index = PortalIndex::default();
+        index.update(&portals);
+        assert_eq!(
+            index.position(&portals, b.id),
+            Some((Dimension::Overworld, 1))
+        );
+        assert_eq!(index.get(&portals, c.id), Some((Dimension::Nether, &c)));
+
+        // Stale lookups fall back to searching.
+        portals.overworld.remove(0);
+        assert_eq!(
+            index.position(&portals, b.id),
+            Some((Dimension::Overworld, 0))
+        );
+        assert_eq!(index.position(&portals, a.id), None);
+        index.update(&portals);
+        assert_eq!(index.positions.len(), 2);
+    }
+
+    #[test]
+    fn test_get_portal() {
+        let a = Portal::new_test(([0, 64, 0], [0, 66, 1]));
+        let b = Portal::new_test(([1, 64, 0], [1, 66, 1]));
+        let mut portals = WorldPortals {
+            overworld: vec![a.clone()],
+            nether: vec![b.clone()],
+        };
+        assert_eq!(portals.get(a.id), Some((Dimension::Overworld, &a)));
+        assert_eq!(portals.get(b.id), Some((Dimension::Nether, &b)));
+        assert_eq!(portals.get(PortalId::new()), None);
+
+        if let Some((_, portal)) = portals.get_mut(b.id) {
+            portal.name = "Hub".to_owned();
+        }
+        assert_eq!(portals.nether[0].name, "Hub");
+    }
+
+    #[test]
+    fn test_display_overrides() {
+        let mut world = World::default();
+        let json = world.to_json(false).expect("serialization failed");
+        assert!(!json.contains("display"));
+
+        world.display.show_all_labels = Some(false);
+        world.display.arrow_coloring = Some(ArrowColoring::ByQuality);
+        let json = world.to_json(false).expect("serialization failed");
+        let loaded = World::from_json(&json).expect("deserialization failed");
+        assert_eq!(loaded.display, world.display);
+    }
+
+    #[test]
+    fn test_show_zy_plot() {
+        let mut world = World::default();
+        let json = world.to_json(false).expect("serialization failed");
+        assert!(!json.contains("show_zy_plot"));
+
+        world.show_zy_plot = Some(false);
+        let json = world.to_json(false).expect("serialization failed");
+        let loaded = World::from_json(&json).expect("deserialization failed");
+        assert_eq!(loaded.show_zy_plot, Some(false));
+        assert_eq!(loaded, World::default());
+    }
+
+    #[test]
+    fn test_entity_y_range() {
+        assert!(Dimension::Nether.entity_y_range().contains(&200.0));
+        assert!(!Dimension::Nether.entity_y_range().contains(&300.0));
+        assert!(Dimension::Overworld.entity_y_range().contains(&300.0));
+        assert_eq!(Dimension::Nether.clamp_entity_y(300.0), 256.0);
+        assert_eq!(Dimension::Overworld.clamp_entity_y(-100.0), -64.0);
+
+        let mut world = World::default();
+        world.test_points.nether = vec![WorldPos {
+            x: 0.0,
+            y: 200.0,
+            z: 0.0,
+        }];
+        world.translate(
+            WorldPos {
+                x: 0.0,
+                y: 100.0,
+                z: 0.0,
+            },
+            Dimension::Nether,
+        );
+        assert_eq!(world.test_points.nether[0].y, 256.0);
+    }
+
+    #[test]
+    fn test_merge() {
+        let mut world = World::default();
+        world.portals.overworld = vec![Portal::new_test(([0, 64, 0], [0, 66, 1]))];
+        world.portals.nether = vec![Portal::new_test(([1, 64, 0], [1, 66, 1]))];
+        world.intended_links =
+            HashMap::from([(world.portals.overworld[0].id, world.portals.nether[0].id)]);
+        let json = world.to_json(true).expect("serialization failed");
+        let mut other = World::from_json(&json).expect("deserialization failed");
+        other.test_points.nether = vec![WorldPos {
+            x: 1.0,
+            y: 2.0,
+            z: 3.0,
+        }];
+
+        world.merge(other);
+        assert_eq!(world.portals.overworld.len(), 2);
+        assert_eq!(world.portals.iter().map(|p| p.id).unique().count(), 4);
+        assert_eq!(
+            world.portals.overworld[0].region,
+            world.portals.overworld[1].region
+        );
+        assert_eq!(world.test_points.nether.len(), 1);
+        // Intended links of merged portals refer to the merged copies.
+        assert_eq!(
+            world.intended_links,
+            HashMap::from([
+                (world.portals.overworld[0].id, world.portals.nether[0].id),
+                (world.portals.overworld[1].id, world.portals.nether[1].id),
+            ]),
+        );
+    }
+
+    #[test]
+    fn test_portal_destination_distances() {
+        let a = Portal::new_test(([10, 64, 0], [10, 66, 1]));
+        let b = Portal::new_test(([0, 64, -5], [1, 66, -5]));
+        let portals = WorldPortals {
+            overworld: vec![a.clone(), b.clone()],
+            nether: vec![],
+        };
+        let destination_region = BlockRegion::from(([0, 64, 0], [3, 66, 3]));
+        // `b` is 5 blocks away along Z, and `a` is 7 blocks away along X.
+        for destinations in [
+            portals.portal_destinations(Dimension::Overworld, destination_region),
+            portals.portal_destinations_naive(Dimension::Overworld, destination_region),
+        ] {
+            assert_eq!(destinations.existing_portals, [&b, &a]);
+            assert_eq!(destinations.distances_sq, [25, 49]);
+            assert!(!destinations.new_portal);
+        }
+    }
+
     #[test]
     fn test_portal_splitting() {
         let big = Portal::new_test(([8, 64, 5], [8, 66, 18])); // nether
@@ -462,6 +1554,90 @@ mod tests {
         assert_eq!(expected, actual);
     }
 
+    /// Returns a grid of small portals in the overworld, which makes the search
+    /// split the destination region many times, along with a large nether
+    /// portal that leads to them.
+    fn pathological_portals() -> (WorldPortals, BlockRegion) {
+        let mut portals = WorldPortals::default();
+        for i in 0..12 {
+            for j in 0..12 {
+                let (x, y, z) = (i * 7 - 40, 60 + (i + j) % 5, j * 5 - 30);
+                portals
+                    .overworld
+                    .push(Portal::new_test(([x, y, z], [x, y + 2, z + 1])));
+            }
+        }
+        let big = Portal::new_test(([0, 64, -10], [0, 84, 10])); // nether
+        let destination_region = big
+            .destination_region(Entity::PLAYER, Dimension::Overworld)
+            .unwrap();
+        (portals, destination_region)
+    }
+
+    #[test]
+    fn test_portal_search_step_limit() {
+        let (portals, destination_region) = pathological_portals();
+        let expected = portals.portal_destinations_naive(Dimension::Overworld, destination_region);
+        let ids = |destinations: &PortalDestinations<'_>| {
+            destinations
+                .existing_portals
+                .iter()
+                .map(|p| p.id)
+                .collect_vec()
+        };
+        for max_steps in [0, 1, 10, DEFAULT_LINK_SEARCH_STEP_LIMIT] {
+            let actual = portals.portal_destinations_with_step_limit(
+                Dimension::Overworld,
+                destination_region,
+                max_steps,
+            );
+            assert_eq!(expected.new_portal, actual.new_portal);
+            assert_eq!(expected.distances_sq, actual.distances_sq);
+            assert_eq!(ids(&expected), ids(&actual));
+        }
+    }
+
+    /// Run with `cargo test --release -- --ignored --nocapture` to compare
+    /// search methods.
+    #[test]
+    #[ignore]
+    fn bench_portal_search() {
+        let (portals, destination_region) = pathological_portals();
+        fn time<T>(name: &str, f: impl Fn() -> T) {
+            let start = std::time::Instant::now();
+            for _ in 0..10 {
+                std::hint::black_box(f());
+            }
+            println!("{name}: {:?} per search", start.elapsed() / 10);
+        }
+        time("naive", || {
+            portals.portal_destinations_naive(Dimension::Overworld, destination_region)
+        });
+        time("fast", || {
+            portals.portal_destinations(Dimension::Overworld, destination_region)
+        });
+        time("fast (unlimited)", || {
+            portals.portal_destinations_with_step_limit(
+                Dimension::Overworld,
+                destination_region,
+                usize::MAX,
+            )
+        });
+        let mut steps = 0;
+        mark_reachable_portals(
+            Dimension::Overworld,
+            destination_region,
+            &portals.overworld,
+            (0..portals.overworld.len()).collect(),
+            &mut vec![false; portals.overworld.len()],
+            &mut vec![i64::MAX; portals.overworld.len()],
+            &mut false,
+            &mut steps,
+            usize::MAX,
+        );
+        println!("{steps} steps");
+    }
+
     proptest! {
         #[test]
         fn proptest_portal_linking(portals in random_portals()) {
@@ -494,6 +1670,8 @@ mod tests {
                         .sorted()
                         .collect_vec(),
                 );
+                assert_eq!(expected.distances_sq, actual.distances_sq);
+                assert!(actual.distances_sq.is_sorted());
             }
         }
     }